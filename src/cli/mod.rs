@@ -1,6 +1,11 @@
 //! CLI logic using clap
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+use crate::core::io::meta::{CipherParams, KdfParams, ModeId, SBoxId};
+use crate::core::crypto::pbkdf2::{DEFAULT_ITERATIONS, MAX_ITERATIONS};
+use crate::core::crypto::scrypt::ScryptParams;
+use crate::core::io::tables::{CustomTables, load_s_box, load_p_box};
+use crate::core::crypto::secret::Secret;
 
 #[derive(Parser)]
 #[clap(author, version, about)]
@@ -11,6 +16,177 @@ pub struct Args {
     pub verbose: bool,
 }
 
+/// Режим шифрования файла
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Mode {
+    Ctr,
+    Cbc,
+    /// CFB с полным блоком обратной связи
+    Cfb,
+    /// CFB с 8-битной обратной связью
+    Cfb8,
+    Ofb,
+}
+
+impl From<Mode> for ModeId {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Ctr => ModeId::CtrHmac,
+            Mode::Cbc => ModeId::CbcHmac,
+            Mode::Cfb => ModeId::CfbHmac,
+            Mode::Cfb8 => ModeId::Cfb8Hmac,
+            Mode::Ofb => ModeId::OfbHmac,
+        }
+    }
+}
+
+/// Набор параметров SPN
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Preset {
+    /// Исходный шифр: 2 раунда, P-box — разворот битов
+    #[value(name = "classic-2r")]
+    Classic2r,
+    /// 10 раундов с транспонирующим P-box
+    #[value(name = "hardened-10r")]
+    Hardened10r,
+    /// 16 раундов с транспонирующим P-box
+    #[value(name = "hardened-16r")]
+    Hardened16r,
+}
+
+impl From<Preset> for CipherParams {
+    fn from(preset: Preset) -> Self {
+        match preset {
+            Preset::Classic2r => CipherParams::CLASSIC,
+            Preset::Hardened10r => CipherParams::hardened(10),
+            Preset::Hardened16r => CipherParams::hardened(16),
+        }
+    }
+}
+
+/// KDF, которым из пароля выводится мастер-ключ
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Kdf {
+    /// Бильярдный KDF (`keygen`)
+    Billiard,
+    /// PBKDF2-HMAC-SHA256
+    Pbkdf2,
+    /// scrypt (расходует память, устойчив к перебору на GPU)
+    Scrypt,
+}
+
+/// Выбор KDF для команд шифрования
+#[derive(clap::Args, Clone, Debug)]
+pub struct KdfArgs {
+    #[clap(long, value_enum, default_value = "billiard")]
+    pub kdf: Kdf,
+    /// Число итераций PBKDF2
+    #[clap(long, default_value_t = DEFAULT_ITERATIONS)]
+    pub iterations: u32,
+    /// log2 N для scrypt
+    #[clap(long, default_value_t = ScryptParams::DEFAULT.log_n)]
+    pub scrypt_log_n: u8,
+    /// Размер блока r для scrypt
+    #[clap(long, default_value_t = ScryptParams::DEFAULT.r)]
+    pub scrypt_r: u32,
+    /// Параллелизм p для scrypt
+    #[clap(long, default_value_t = ScryptParams::DEFAULT.p)]
+    pub scrypt_p: u32,
+    /// Подать на вход scrypt результат бильярдного KDF вместо пароля
+    #[clap(long)]
+    pub billiard_prehash: bool,
+}
+
+impl KdfArgs {
+    /// Параметры KDF для заголовка; флаги других KDF игнорируются
+    pub fn params(&self) -> Result<KdfParams, String> {
+        match self.kdf {
+            Kdf::Billiard => Ok(KdfParams::Billiard),
+            Kdf::Pbkdf2 if self.iterations == 0 => Err("PBKDF2 iteration count must be positive".into()),
+            Kdf::Pbkdf2 if self.iterations > MAX_ITERATIONS =>
+                Err(format!("PBKDF2 iteration count must not exceed {}", MAX_ITERATIONS)),
+            Kdf::Pbkdf2 => Ok(KdfParams::Pbkdf2 { iterations: self.iterations }),
+            Kdf::Scrypt => Ok(KdfParams::Scrypt {
+                params: ScryptParams::new(self.scrypt_log_n, self.scrypt_r, self.scrypt_p)?,
+                billiard_prehash: self.billiard_prehash,
+            }),
+        }
+    }
+}
+
+impl Default for KdfArgs {
+    fn default() -> Self {
+        KdfArgs {
+            kdf: Kdf::Billiard,
+            iterations: DEFAULT_ITERATIONS,
+            scrypt_log_n: ScryptParams::DEFAULT.log_n,
+            scrypt_r: ScryptParams::DEFAULT.r,
+            scrypt_p: ScryptParams::DEFAULT.p,
+            billiard_prehash: false,
+        }
+    }
+}
+
+/// Параметры SPN из пресета и флагов командной строки
+pub fn cipher_params(preset: Preset, keyed_s_box: bool) -> CipherParams {
+    let mut params = CipherParams::from(preset);
+    if keyed_s_box {
+        params.s_box = SBoxId::Keyed;
+    }
+    params
+}
+
+/// Загружает пользовательские таблицы, если они заданы
+pub fn load_tables(s_box: Option<&Path>, p_box: Option<&Path>) -> Result<CustomTables, String> {
+    Ok(CustomTables {
+        s_box: s_box.map(load_s_box).transpose()?,
+        p_box: p_box.map(load_p_box).transpose()?,
+    })
+}
+
+/// Диапазон открытого текста `START:LEN` в байтах
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub len: u64,
+}
+
+impl std::str::FromStr for ByteRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, len) = s.split_once(':')
+            .ok_or_else(|| format!("Expected START:LEN, got '{}'", s))?;
+        let parse = |v: &str| v.trim().parse::<u64>().map_err(|_| format!("Invalid number '{}'", v));
+        Ok(ByteRange { start: parse(start)?, len: parse(len)? })
+    }
+}
+
+/// Общий секрет для `mac`: строка из `--key` или содержимое `--key-file`
+pub fn load_mac_key(key: Option<&str>, key_file: Option<&Path>) -> Result<Secret<Vec<u8>>, String> {
+    match (key, key_file) {
+        (Some(key), None) => Ok(Secret::new(key.as_bytes().to_vec())),
+        (None, Some(path)) => std::fs::read(path)
+            .map(Secret::new)
+            .map_err(|e| format!("Error reading key file '{}': {}", path.display(), e)),
+        _ => Err("Specify exactly one of --key and --key-file".into()),
+    }
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return Err(format!("Invalid hex string '{}'", text));
+    }
+    (0..text.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).map_err(|_| format!("Invalid hex string '{}'", text)))
+        .collect()
+}
+
 #[derive(Subcommand)]
 pub enum Command {
     EncryptFile {
@@ -20,6 +196,21 @@ pub enum Command {
         input: PathBuf,
         #[clap(short, long)]
         output: PathBuf,
+        #[clap(short, long, value_enum, default_value = "ctr")]
+        mode: Mode,
+        #[clap(long, value_enum, default_value = "classic-2r")]
+        preset: Preset,
+        /// S-box генерируется из ключа вместо S-box AES
+        #[clap(long)]
+        keyed_s_box: bool,
+        /// Файл с S-box (hex-текст или JSON-массив)
+        #[clap(long)]
+        s_box: Option<PathBuf>,
+        /// Файл с P-box (hex-текст или JSON-массив)
+        #[clap(long)]
+        p_box: Option<PathBuf>,
+        #[clap(flatten)]
+        kdf: KdfArgs,
     },
     DecryptFile {
         #[clap(short, long)]
@@ -28,6 +219,18 @@ pub enum Command {
         input: PathBuf,
         #[clap(short, long)]
         output: PathBuf,
+        /// Расшифровать только байты START..START+LEN (только для CTR)
+        #[clap(long, value_name = "START:LEN")]
+        range: Option<ByteRange>,
+        /// Файл с S-box (hex-текст или JSON-массив)
+        #[clap(long)]
+        s_box: Option<PathBuf>,
+        /// Файл с P-box (hex-текст или JSON-массив)
+        #[clap(long)]
+        p_box: Option<PathBuf>,
+        /// Расшифровывать файлы старого формата v0 без тега (целостность не проверяется)
+        #[clap(long)]
+        legacy: bool,
     },
     EncryptDir {
         #[clap(short, long)]
@@ -36,6 +239,21 @@ pub enum Command {
         input: PathBuf,
         #[clap(short, long)]
         output: PathBuf,
+        #[clap(short, long, value_enum, default_value = "ctr")]
+        mode: Mode,
+        #[clap(long, value_enum, default_value = "classic-2r")]
+        preset: Preset,
+        /// S-box генерируется из ключа вместо S-box AES
+        #[clap(long)]
+        keyed_s_box: bool,
+        /// Файл с S-box (hex-текст или JSON-массив)
+        #[clap(long)]
+        s_box: Option<PathBuf>,
+        /// Файл с P-box (hex-текст или JSON-массив)
+        #[clap(long)]
+        p_box: Option<PathBuf>,
+        #[clap(flatten)]
+        kdf: KdfArgs,
     },
     DecryptDir {
         #[clap(short, long)]
@@ -44,5 +262,39 @@ pub enum Command {
         input: PathBuf,
         #[clap(short, long)]
         output: PathBuf,
+        /// Файл с S-box (hex-текст или JSON-массив)
+        #[clap(long)]
+        s_box: Option<PathBuf>,
+        /// Файл с P-box (hex-текст или JSON-массив)
+        #[clap(long)]
+        p_box: Option<PathBuf>,
+        /// Расшифровывать файлы старого формата v0 без тега (целостность не проверяется)
+        #[clap(long)]
+        legacy: bool,
     },
-}
\ No newline at end of file
+    /// HMAC-SHA256 файла с общим секретом; тег печатается в hex
+    Mac {
+        /// Общий секрет строкой
+        #[clap(short, long, conflicts_with = "key_file", required_unless_present = "key_file")]
+        key: Option<String>,
+        /// Файл, содержимое которого — общий секрет
+        #[clap(long)]
+        key_file: Option<PathBuf>,
+        #[clap(short, long)]
+        input: PathBuf,
+    },
+    /// Проверка HMAC-SHA256 файла; при несовпадении код выхода 1
+    MacVerify {
+        /// Общий секрет строкой
+        #[clap(short, long, conflicts_with = "key_file", required_unless_present = "key_file")]
+        key: Option<String>,
+        /// Файл, содержимое которого — общий секрет
+        #[clap(long)]
+        key_file: Option<PathBuf>,
+        #[clap(short, long)]
+        input: PathBuf,
+        /// Ожидаемый тег в hex (можно усечённый, не короче 16 байт)
+        #[clap(short, long)]
+        tag: String,
+    },
+}
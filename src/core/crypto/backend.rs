@@ -0,0 +1,205 @@
+//! Платформенные реализации операций над блоками: SSE2/SSSE3/AVX2 на x86_64,
+//! NEON на aarch64 и переносимая скалярная для остальных целей (wasm и т.п.).
+//!
+//! `Backend::detect` выбирает лучшую доступную реализацию во время выполнения;
+//! все варианты дают одинаковый результат. Вариант можно назвать и вручную,
+//! поэтому перед интринсиками поддержка процессора проверяется ещё раз:
+//! без неё операция выполняется скалярно.
+use cfg_if::cfg_if;
+
+/// Число блоков, которые обрабатываются за один проход
+pub const BATCH: usize = 8;
+
+/// Пачка блоков, обрабатываемая одним вызовом
+pub type Batch = [[u8; 16]; BATCH];
+
+/// Реализация операций над блоком
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Переносимая реализация без интринсиков
+    Scalar,
+    #[cfg(target_arch = "x86_64")]
+    Sse2,
+    /// SSE2 плюс S-box через `pshufb`
+    #[cfg(target_arch = "x86_64")]
+    Ssse3,
+    /// S-box через `vpshufb` по 32 байта
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+    #[cfg(target_arch = "aarch64")]
+    Neon,
+}
+
+impl Backend {
+    /// Лучшая реализация, поддерживаемая процессором
+    pub fn detect() -> Self {
+        cfg_if! {
+            if #[cfg(target_arch = "x86_64")] {
+                if std::arch::is_x86_feature_detected!("avx2") {
+                    return Backend::Avx2;
+                }
+                if std::arch::is_x86_feature_detected!("ssse3") {
+                    return Backend::Ssse3;
+                }
+                if std::arch::is_x86_feature_detected!("sse2") {
+                    return Backend::Sse2;
+                }
+            } else if #[cfg(target_arch = "aarch64")] {
+                if std::arch::is_aarch64_feature_detected!("neon") {
+                    return Backend::Neon;
+                }
+            }
+        }
+        Backend::Scalar
+    }
+
+    /// Все реализации, которые можно использовать на этой машине
+    pub fn available() -> Vec<Self> {
+        // На целях без SIMD (wasm и т.п.) список не пополняется
+        #[cfg_attr(not(any(target_arch = "x86_64", target_arch = "aarch64")), allow(unused_mut))]
+        let mut backends = vec![Backend::Scalar];
+        cfg_if! {
+            if #[cfg(target_arch = "x86_64")] {
+                let features = [
+                    (Backend::Sse2, std::arch::is_x86_feature_detected!("sse2")),
+                    (Backend::Ssse3, std::arch::is_x86_feature_detected!("ssse3")),
+                    (Backend::Avx2, std::arch::is_x86_feature_detected!("avx2")),
+                ];
+                backends.extend(features.iter().filter(|(_, ok)| *ok).map(|(b, _)| *b));
+            } else if #[cfg(target_arch = "aarch64")] {
+                if std::arch::is_aarch64_feature_detected!("neon") {
+                    backends.push(Backend::Neon);
+                }
+            }
+        }
+        backends
+    }
+
+    /// `a ^= b`
+    #[inline(always)]
+    pub fn xor_block(self, a: &mut [u8; 16], b: &[u8; 16]) {
+        match self {
+            // Проверки кэшируются std, а для включённых при сборке расширений
+            // сводятся к константе
+            #[cfg(target_arch = "x86_64")]
+            Backend::Sse2 | Backend::Ssse3 | Backend::Avx2
+                if std::arch::is_x86_feature_detected!("sse2") => unsafe { xor_sse2(a, b) },
+            #[cfg(target_arch = "aarch64")]
+            Backend::Neon if std::arch::is_aarch64_feature_detected!("neon") => unsafe { xor_neon(a, b) },
+            _ => xor_scalar(a, b),
+        }
+    }
+
+    /// Применяет `s_box` ко всем байтам пачки
+    #[inline(always)]
+    pub fn substitute_batch(self, s_box: &[u8; 256], blocks: &mut Batch) {
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            Backend::Ssse3 if std::arch::is_x86_feature_detected!("ssse3") => unsafe {
+                substitute_ssse3(s_box, blocks)
+            },
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 if std::arch::is_x86_feature_detected!("avx2") => unsafe {
+                substitute_avx2(s_box, blocks)
+            },
+            _ => blocks.iter_mut().flatten().for_each(|byte| *byte = s_box[*byte as usize]),
+        }
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::detect()
+    }
+}
+
+#[inline(always)]
+fn xor_scalar(a: &mut [u8; 16], b: &[u8; 16]) {
+    let res = u128::from_ne_bytes(*a) ^ u128::from_ne_bytes(*b);
+    *a = res.to_ne_bytes();
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn xor_sse2(a: &mut [u8; 16], b: &[u8; 16]) {
+    use std::arch::x86_64::{__m128i, _mm_loadu_si128, _mm_storeu_si128, _mm_xor_si128};
+    let a_ptr = a.as_mut_ptr() as *mut __m128i;
+    let b_ptr = b.as_ptr() as *const __m128i;
+    unsafe {
+        let a_vec = _mm_loadu_si128(a_ptr);
+        let b_vec = _mm_loadu_si128(b_ptr);
+        let res = _mm_xor_si128(a_vec, b_vec);
+        _mm_storeu_si128(a_ptr, res);
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+unsafe fn xor_neon(a: &mut [u8; 16], b: &[u8; 16]) {
+    use std::arch::aarch64::{veorq_u8, vld1q_u8, vst1q_u8};
+    unsafe {
+        let a_vec = vld1q_u8(a.as_ptr());
+        let b_vec = vld1q_u8(b.as_ptr());
+        vst1q_u8(a.as_mut_ptr(), veorq_u8(a_vec, b_vec));
+    }
+}
+
+// S-box через перестановку байт: таблица делится на 16 строк по 16 байт
+// (строка — старшая полубайта). Для строки `h` индекс `x ^ (h << 4)` меньше 16
+// только у байтов из этой строки; после насыщающего сложения с 0x70 у остальных
+// выставлен старший бит, и `pshufb` даёт для них ноль. OR по всем строкам
+// собирает результат, причём чтения таблицы не зависят от данных
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn substitute_ssse3(s_box: &[u8; 256], blocks: &mut Batch) {
+    use std::arch::x86_64::*;
+    unsafe {
+        let bias = _mm_set1_epi8(0x70);
+        let ptr = blocks.as_mut_ptr() as *mut __m128i;
+        let mut x = [_mm_setzero_si128(); BATCH];
+        for (i, v) in x.iter_mut().enumerate() {
+            *v = _mm_loadu_si128(ptr.add(i));
+        }
+        let mut out = [_mm_setzero_si128(); BATCH];
+        for h in 0..16 {
+            let row = _mm_loadu_si128(s_box.as_ptr().add(h * 16) as *const __m128i);
+            let high = _mm_set1_epi8((h << 4) as i8);
+            for (o, v) in out.iter_mut().zip(x.iter()) {
+                let idx = _mm_adds_epu8(_mm_xor_si128(*v, high), bias);
+                *o = _mm_or_si128(*o, _mm_shuffle_epi8(row, idx));
+            }
+        }
+        for (i, o) in out.iter().enumerate() {
+            _mm_storeu_si128(ptr.add(i), *o);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn substitute_avx2(s_box: &[u8; 256], blocks: &mut Batch) {
+    use std::arch::x86_64::*;
+    const LANES: usize = BATCH / 2;
+    unsafe {
+        let bias = _mm256_set1_epi8(0x70);
+        let ptr = blocks.as_mut_ptr() as *mut __m256i;
+        let mut x = [_mm256_setzero_si256(); LANES];
+        for (i, v) in x.iter_mut().enumerate() {
+            *v = _mm256_loadu_si256(ptr.add(i));
+        }
+        let mut out = [_mm256_setzero_si256(); LANES];
+        for h in 0..16 {
+            let row = _mm256_broadcastsi128_si256(
+                _mm_loadu_si128(s_box.as_ptr().add(h * 16) as *const __m128i));
+            let high = _mm256_set1_epi8((h << 4) as i8);
+            for (o, v) in out.iter_mut().zip(x.iter()) {
+                let idx = _mm256_adds_epu8(_mm256_xor_si256(*v, high), bias);
+                *o = _mm256_or_si256(*o, _mm256_shuffle_epi8(row, idx));
+            }
+        }
+        for (i, o) in out.iter().enumerate() {
+            _mm256_storeu_si256(ptr.add(i), *o);
+        }
+    }
+}
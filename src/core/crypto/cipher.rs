@@ -1,148 +1,442 @@
-use super::{s_box::S_BOX, p_box::P_BOX};
+use super::{s_box::{S_BOX, generate_s_box, substitute, substitute_ct}, p_box::{P_BOX, TRANSPOSE_P_BOX}, sha256::Sha256};
+use super::permutation::Permutation;
+use super::backend::{Backend, Batch, BATCH};
+use super::secret::{Secret, Zeroize};
 use arrayref::array_ref;
 use rayon::prelude::*;
-use std::arch::x86_64::{__m128i, _mm_loadu_si128, _mm_storeu_si128, _mm_xor_si128};
-use core::array;
 
-const BLOCK_SIZE: usize = 16;
+pub const BLOCK_SIZE: usize = 16;
+/// Верхняя граница числа раундов
+pub const MAX_ROUNDS: usize = 64;
+/// Размер части буфера CTR, которую обрабатывает одна задача rayon
+const PAR_CHUNK: usize = 16 * 1024;
+/// Режим постоянного времени по умолчанию включается фичей `constant-time`
+pub const CONSTANT_TIME_DEFAULT: bool = cfg!(feature = "constant-time");
 
-#[derive(Copy, Clone)] // Добавлены трейты Copy и Clone
-struct PermutationInfo {
-    old_byte: usize,
-    old_bit: u8,
+fn is_permutation<const N: usize, T: Copy + Into<usize>>(table: &[T; N]) -> bool {
+    let mut seen = [false; N];
+    table.iter().all(|&v| {
+        let v: usize = v.into();
+        v < N && !std::mem::replace(&mut seen[v], true)
+    })
+}
+
+/// Способ получения раундовых ключей из 32-байтного мастер-ключа
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySchedule {
+    /// Исходная схема: раунды по очереди берут половины ключа, без отбеливания
+    Split = 0,
+    /// `expand_key`: N + 1 независимых ключей, нулевой — отбеливающий
+    Expanded = 1,
+}
+
+/// Разворачивает мастер-ключ в `rounds + 1` раундовых ключей:
+/// `K_i = SHA256("spn key schedule" || key || i)[..16]`, `i` — 32-битное big-endian.
+/// `K_0` накладывается на блок до первого раунда, `K_i` — после раунда `i`.
+/// Хеш разрывает линейную связь между ключами, так что разность мастер-ключей
+/// не переходит в предсказуемую разность раундовых ключей
+pub fn expand_key(key: &[u8; 32], rounds: usize) -> Vec<[u8; 16]> {
+    (0..=rounds as u32)
+        .map(|i| {
+            let mut hasher = Sha256::new();
+            hasher.update(b"spn key schedule").update(key).update(&i.to_be_bytes());
+            let digest = hasher.finalize();
+            *array_ref!(digest, 0, 16)
+        })
+        .collect()
+}
+
+/// Параметры SPN: число раундов, S-box, P-box и схема раундовых ключей
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpnParams {
+    pub rounds: usize,
+    pub s_box: [u8; 256],
+    pub p_box: [usize; 128],
+    pub key_schedule: KeySchedule,
+    /// Вычислять раунды без обращений к таблицам по секретным индексам
+    /// (медленнее; на результат не влияет и в заголовок не пишется)
+    pub constant_time: bool,
+}
+
+impl Drop for SpnParams {
+    fn drop(&mut self) {
+        self.s_box.zeroize();
+    }
+}
+
+impl Default for SpnParams {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+impl SpnParams {
+    /// Исходный шифр: 2 раунда, S-box AES, P-box — разворот битов, половины ключа
+    pub fn classic() -> Self {
+        SpnParams { rounds: 2, s_box: S_BOX, p_box: P_BOX, key_schedule: KeySchedule::Split,
+            constant_time: CONSTANT_TIME_DEFAULT }
+    }
+
+    /// S-box AES, транспонирующий P-box и развёрнутый ключ с отбеливанием
+    pub fn hardened(rounds: usize) -> Self {
+        SpnParams { rounds, s_box: S_BOX, p_box: TRANSPOSE_P_BOX, key_schedule: KeySchedule::Expanded,
+            constant_time: CONSTANT_TIME_DEFAULT }
+    }
+
+    pub fn with_rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    pub fn with_s_box(mut self, s_box: [u8; 256]) -> Self {
+        self.s_box = s_box;
+        self
+    }
+
+    pub fn with_p_box(mut self, p_box: [usize; 128]) -> Self {
+        self.p_box = p_box;
+        self
+    }
+
+    /// S-box, сгенерированный из ключа (`generate_s_box`)
+    pub fn with_keyed_s_box(mut self, key: &[u8; 32]) -> Self {
+        self.s_box = generate_s_box(key);
+        self
+    }
+
+    pub fn with_key_schedule(mut self, key_schedule: KeySchedule) -> Self {
+        self.key_schedule = key_schedule;
+        self
+    }
+
+    /// Защита от атак по времени через кэш: S-box вычисляется полным
+    /// просмотром таблицы с маской, перестановка — побитово
+    pub fn with_constant_time(mut self, constant_time: bool) -> Self {
+        self.constant_time = constant_time;
+        self
+    }
+
+    /// Таблицы должны быть перестановками, иначе блок нельзя расшифровать
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.rounds == 0 || self.rounds > MAX_ROUNDS {
+            return Err("Round count out of range");
+        }
+        if !is_permutation(&self.s_box) {
+            return Err("S-box is not a permutation");
+        }
+        if !is_permutation(&self.p_box) {
+            return Err("P-box is not a permutation");
+        }
+        Ok(())
+    }
+}
+
+/// Раскладка блока счётчика CTR: IV делится на nonce, который не меняется,
+/// и счётчик в младших байтах, к которому прибавляется номер блока
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterLayout {
+    /// `nonce (12) || counter (4)` — исходная раскладка, только для файлов v0.
+    /// Не больше 2^32 блоков (64 ГиБ): дальше гамма повторилась бы
+    Legacy32,
+    /// `nonce (8) || counter (8)`: номер блока — `u64`, счётчик не заворачивается
+    Wide64,
+}
+
+impl CounterLayout {
+    /// Предел числа блоков гаммы на один ключ и IV
+    pub const fn max_blocks(self) -> u64 {
+        match self {
+            Self::Legacy32 => 1 << 32,
+            Self::Wide64 => u64::MAX,
+        }
+    }
+
+    /// Блок счётчика номер `block`: счётчик складывается по модулю своей ширины,
+    /// nonce остаётся прежним
+    fn counter_block(self, iv: u128, block: u64) -> [u8; 16] {
+        let mask = match self {
+            Self::Legacy32 => u32::MAX as u128,
+            Self::Wide64 => u64::MAX as u128,
+        };
+        ((iv & !mask) | (iv.wrapping_add(block as u128) & mask)).to_be_bytes()
+    }
+
+    /// Блоки с `first_block` на `len` байт не выходят за предел счётчика
+    fn check(self, first_block: u64, len: u128) -> Result<(), &'static str> {
+        let end = first_block as u128 + len.div_ceil(BLOCK_SIZE as u128);
+        if end > self.max_blocks() as u128 {
+            return Err("CTR block counter limit exceeded");
+        }
+        Ok(())
+    }
+}
+
+/// Состояние CTR между буферами: гамма продолжается с того байта, где остановилась
+pub struct CtrStream<'a> {
+    cipher: &'a Cipher,
+    iv: [u8; 16],
+    layout: CounterLayout,
+    offset: u64,
+}
+
+impl<'a> CtrStream<'a> {
+    pub fn new(cipher: &'a Cipher, iv: [u8; 16]) -> Self {
+        Self::with_layout(cipher, iv, CounterLayout::Wide64)
+    }
+
+    pub fn with_layout(cipher: &'a Cipher, iv: [u8; 16], layout: CounterLayout) -> Self {
+        CtrStream { cipher, iv, layout, offset: 0 }
+    }
+
+    /// Буферы могут быть любой длины, в том числе не кратной размеру блока
+    pub fn apply(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        self.cipher.apply_keystream_with(self.layout, &self.iv, self.offset, buf)?;
+        self.offset += buf.len() as u64;
+        Ok(())
+    }
 }
 
-// Предварительно вычисленная таблица перестановок с использованием array::from_fn
-const PERMUTATION_TABLE: [[PermutationInfo; 8]; 16] = {
-    let mut table = [[PermutationInfo { old_byte: 0, old_bit: 0 }; 8]; 16];
-    
-    // Вычисление значений для каждого элемента в константном контексте
-    let mut new_byte = 0;
-    while new_byte < 16 {
-        let mut new_bit = 0;
-        while new_bit < 8 {
-            let pos = new_byte * 8 + (7 - new_bit);
-            let old_pos = P_BOX[pos];
-            table[new_byte][new_bit] = PermutationInfo {
-                old_byte: old_pos / 8,
-                old_bit: (7 - (old_pos % 8)) as u8,
-            };
-            new_bit += 1;
-        }
-        new_byte += 1;
-    }
-    table
-};
 pub struct Cipher {
-    key: [u8; 32],
-    key1: [u8; 16],
-    key2: [u8; 16],
+    whitening: Option<Secret<[u8; 16]>>,
+    round_keys: Secret<Vec<[u8; 16]>>,
+    // S-box может быть выведен из ключа
+    s_box: Secret<[u8; 256]>,
+    inv_s_box: Secret<[u8; 256]>,
+    permutation: Permutation,
+    inv_permutation: Permutation,
+    constant_time: bool,
+    backend: Backend,
 }
 
 impl Cipher {
+    /// Исходный двухраундовый шифр (`SpnParams::classic`)
     pub fn new(key: [u8; 32]) -> Self {
-        let key1 = *array_ref!(key, 0, 16);
-        let key2 = *array_ref!(key, 16, 16);
-        Cipher { key, key1, key2 }
+        Self::build(key, &SpnParams::classic())
     }
 
-    pub fn encrypt(&self, data: &[u8], iv: &[u8; 16]) -> Vec<u8> {
-        let (nonce, counter_part) = iv.split_at(12);
-        let initial_counter = u32::from_be_bytes(counter_part.try_into().unwrap()) as u64;
+    pub fn with_params(key: [u8; 32], params: &SpnParams) -> Result<Self, &'static str> {
+        params.validate()?;
+        Ok(Self::build(key, params))
+    }
 
-        let encrypted_chunks: Vec<Vec<u8>> = data
-            .par_chunks(BLOCK_SIZE)
-            .enumerate()
-            .map(|(i, chunk)| {
-                let mut ctr_block = [0u8; 16];
-                ctr_block[..12].copy_from_slice(nonce);
-                let counter = initial_counter + i as u64;
-                ctr_block[12..].copy_from_slice(&counter.to_be_bytes()[4..]);
+    fn build(key: [u8; 32], params: &SpnParams) -> Self {
+        let key = Secret::new(key);
+        let (whitening, round_keys) = match params.key_schedule {
+            // Для двух раундов это в точности прежние key1 и key2
+            KeySchedule::Split => {
+                let halves = Secret::new([*array_ref!(key, 0, 16), *array_ref!(key, 16, 16)]);
+                (None, Secret::new((0..params.rounds).map(|i| halves[i % 2]).collect()))
+            }
+            KeySchedule::Expanded => {
+                let mut keys = Secret::new(expand_key(&key, params.rounds));
+                let whitening = Secret::new(keys.remove(0));
+                (Some(whitening), keys)
+            }
+        };
+
+        let mut inv_s_box = Secret::new([0u8; 256]);
+        for (i, &s) in params.s_box.iter().enumerate() {
+            inv_s_box[s as usize] = i as u8;
+        }
+
+        Cipher {
+            whitening,
+            round_keys,
+            s_box: Secret::new(params.s_box),
+            inv_s_box,
+            permutation: Permutation::forward(&params.p_box),
+            inv_permutation: Permutation::inverse(&params.p_box),
+            constant_time: params.constant_time,
+            backend: Backend::detect(),
+        }
+    }
+
+    /// Накладывает CTR-гамму на `buf` на месте, как если бы `buf` начинался с байта
+    /// `offset` потока. Шифрование и расшифрование — одна и та же операция; `nonce`
+    /// — начальное значение счётчика (IV), в результат он не записывается.
+    /// Вызовы с соседними смещениями дают тот же результат, что и один общий вызов
+    pub fn apply_keystream(&self, nonce: &[u8; 16], offset: u64, buf: &mut [u8]) -> Result<(), &'static str> {
+        self.apply_keystream_with(CounterLayout::Wide64, nonce, offset, buf)
+    }
+
+    /// `apply_keystream` с явной раскладкой счётчика; гамма за её пределом — ошибка
+    pub fn apply_keystream_with(
+        &self,
+        layout: CounterLayout,
+        nonce: &[u8; 16],
+        offset: u64,
+        buf: &mut [u8],
+    ) -> Result<(), &'static str> {
+        let mut first_block = offset / BLOCK_SIZE as u64;
+        let skip = (offset % BLOCK_SIZE as u64) as usize;
+        layout.check(first_block, (skip + buf.len()) as u128)?;
 
-                let mut key_stream = [0u8; BLOCK_SIZE];
-                self.process_block(&mut ctr_block, &mut key_stream);
+        // Начало посреди блока: хвост этого блока гаммы накладывается отдельно
+        let rest = if skip == 0 {
+            buf
+        } else {
+            let (head, rest) = buf.split_at_mut((BLOCK_SIZE - skip).min(buf.len()));
+            let mut key_stream = [0u8; BLOCK_SIZE];
+            self.xor_keystream(layout, nonce, first_block, &mut key_stream);
+            head.iter_mut()
+                .zip(&key_stream[skip..])
+                .for_each(|(d, k)| *d ^= k);
+            first_block += 1;
+            rest
+        };
+        self.xor_keystream(layout, nonce, first_block, rest);
+        Ok(())
+    }
 
-                chunk.iter()
-                    .zip(key_stream.iter())
-                    .map(|(d, k)| d ^ k)
-                    .collect()
-            })
-            .collect();
+    /// CTR-режим без префикса IV: `apply_keystream` с нулевого смещения в новый буфер
+    pub fn apply_ctr(&self, data: &[u8], iv: &[u8; 16]) -> Result<Vec<u8>, &'static str> {
+        let mut out = data.to_vec();
+        self.apply_keystream(iv, 0, &mut out)?;
+        Ok(out)
+    }
 
-        let mut encrypted = iv.to_vec();
-        encrypted.extend(encrypted_chunks.concat());
-        encrypted
+    /// Накладывает CTR-гамму (`CounterLayout::Wide64`) на `buf` на месте, начиная
+    /// с блока номер `first_block`. Блоки обрабатываются параллельно внутри буфера
+    pub fn apply_ctr_at(&self, iv: &[u8; 16], first_block: u64, buf: &mut [u8]) -> Result<(), &'static str> {
+        let layout = CounterLayout::Wide64;
+        layout.check(first_block, buf.len() as u128)?;
+        self.xor_keystream(layout, iv, first_block, buf);
+        Ok(())
     }
 
+    /// Номера блоков уже проверены `CounterLayout::check`
+    fn xor_keystream(&self, layout: CounterLayout, iv: &[u8; 16], first_block: u64, buf: &mut [u8]) {
+        let iv = u128::from_be_bytes(*iv);
+
+        // Каждой задаче rayon достаётся PAR_CHUNK байт, внутри — пачки по BATCH блоков
+        buf.par_chunks_mut(PAR_CHUNK)
+            .enumerate()
+            .for_each(|(i, chunk)| {
+                let mut counter = first_block + (i * PAR_CHUNK / BLOCK_SIZE) as u64;
+                for part in chunk.chunks_mut(BATCH * BLOCK_SIZE) {
+                    let mut key_stream: Batch = [[0u8; BLOCK_SIZE]; BATCH];
+                    for block in key_stream.iter_mut() {
+                        *block = layout.counter_block(iv, counter);
+                        counter = counter.wrapping_add(1);
+                    }
+                    self.process_batch(&mut key_stream);
+
+                    part.iter_mut()
+                        .zip(key_stream.iter().flatten())
+                        .for_each(|(d, k)| *d ^= k);
+                }
+            });
+    }
+
+    /// Шифрует `data` в CTR и возвращает `iv || шифртекст`. IV записывается открыто,
+    /// чтобы его не нужно было хранить отдельно; сам шифртекст — это
+    /// `apply_keystream(iv, 0, data)`
+    pub fn encrypt(&self, data: &[u8], iv: &[u8; 16]) -> Result<Vec<u8>, &'static str> {
+        let mut encrypted = Vec::with_capacity(BLOCK_SIZE + data.len());
+        encrypted.extend_from_slice(iv);
+        encrypted.extend_from_slice(data);
+        self.apply_keystream(iv, 0, &mut encrypted[BLOCK_SIZE..])?;
+        Ok(encrypted)
+    }
+
+    /// Обратная к `encrypt`: ожидает `iv || шифртекст`. Первые 16 байт `data` не
+    /// используются — гамма строится из переданного `iv`, который должен совпадать
+    /// с префиксом. Без префикса расшифровывает `apply_keystream`
     pub fn decrypt(&self, data: &[u8], iv: &[u8; 16]) -> Result<Vec<u8>, &'static str> {
         if data.len() < BLOCK_SIZE {
             return Err("Invalid ciphertext length");
         }
 
-        let (nonce, counter_part) = iv.split_at(12);
-        let initial_counter = u32::from_be_bytes(counter_part.try_into().unwrap()) as u64;
+        self.apply_ctr(&data[BLOCK_SIZE..], iv)
+    }
 
-        let decrypted_chunks: Vec<Vec<u8>> = data[BLOCK_SIZE..]
-            .par_chunks(BLOCK_SIZE)
-            .enumerate()
-            .map(|(i, chunk)| {
-                let mut ctr_block = [0u8; 16];
-                ctr_block[..12].copy_from_slice(nonce);
-                let counter = initial_counter + i as u64;
-                ctr_block[12..].copy_from_slice(&counter.to_be_bytes()[4..]);
+    /// Шифрует один блок на месте
+    pub fn encrypt_block(&self, block: &mut [u8; 16]) {
+        let input = *block;
+        self.process_block(&input, block);
+    }
 
-                let mut key_stream = [0u8; BLOCK_SIZE];
-                self.process_block(&mut ctr_block, &mut key_stream);
+    /// Обратное преобразование к `encrypt_block`: раунды в обратном порядке,
+    /// в каждом XOR с ключом, обратная перестановка и обратный S-box
+    pub fn decrypt_block(&self, block: &mut [u8; 16]) {
+        for round_key in self.round_keys.iter().rev() {
+            self.backend.xor_block(block, round_key);
+            if self.constant_time {
+                self.inv_permutation.apply_bitwise(block);
+                substitute_ct(&self.inv_s_box, block);
+            } else {
+                self.inv_permutation.apply(block);
+                substitute(&self.inv_s_box, block);
+            }
+        }
+        if let Some(whitening) = &self.whitening {
+            self.backend.xor_block(block, whitening);
+        }
+    }
 
-                chunk.iter()
-                    .zip(key_stream.iter())
-                    .map(|(c, k)| c ^ k)
-                    .collect()
-            })
-            .collect();
+    pub fn rounds(&self) -> usize {
+        self.round_keys.len()
+    }
 
-        Ok(decrypted_chunks.concat())
+    pub fn is_constant_time(&self) -> bool {
+        self.constant_time
     }
 
-    #[inline(always)]
-    fn process_block(&self, input: &mut [u8; 16], output: &mut [u8; 16]) {
-        let mut block = *input;
-        
-        // Первый раунд
-        block.iter_mut().for_each(|byte| *byte = S_BOX[*byte as usize]);
-        permute_bits(&mut block);
-        xor_bytes_simd(&mut block, &self.key1);
-
-        // Второй раунд
-        block.iter_mut().for_each(|byte| *byte = S_BOX[*byte as usize]);
-        permute_bits(&mut block);
-        xor_bytes_simd(&mut block, &self.key2);
-        
-        output.copy_from_slice(&block);
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Заменяет автоматически выбранную реализацию XOR (например, чтобы сравнить
+    /// SIMD со скалярной); допустимы только варианты из `Backend::available`
+    pub fn with_backend(mut self, backend: Backend) -> Result<Self, &'static str> {
+        if !Backend::available().contains(&backend) {
+            return Err("Backend is not supported by this CPU");
+        }
+        self.backend = backend;
+        Ok(self)
     }
-}
 
-#[inline(always)]
-fn permute_bits(block: &mut [u8; 16]) {
-    let mut new_block = [0u8; 16];
-    for (new_byte, bits) in PERMUTATION_TABLE.iter().enumerate() {
-        new_block[new_byte] = bits.iter().enumerate()
-            .fold(0u8, |acc, (new_bit, info)| {
-                let bit = (block[info.old_byte] >> info.old_bit) & 1;
-                acc | (bit << (7 - new_bit))
+    /// То же, что `process_block` для каждого блока, но раунд выполняется сразу
+    /// для всей пачки: S-box векторизуется, а независимые блоки загружают конвейер
+    fn process_batch(&self, blocks: &mut Batch) {
+        if self.constant_time {
+            blocks.iter_mut().for_each(|block| {
+                let input = *block;
+                self.process_block(&input, block);
             });
+            return;
+        }
+
+        if let Some(whitening) = &self.whitening {
+            blocks.iter_mut().for_each(|block| self.backend.xor_block(block, whitening));
+        }
+        for round_key in self.round_keys.iter() {
+            self.backend.substitute_batch(&self.s_box, blocks);
+            for block in blocks.iter_mut() {
+                self.permutation.apply(block);
+                self.backend.xor_block(block, round_key);
+            }
+        }
     }
-    *block = new_block;
-}
 
-#[inline(always)]
-fn xor_bytes_simd(a: &mut [u8; 16], b: &[u8; 16]) {
-    unsafe {
-        let a_ptr = a.as_mut_ptr() as *mut __m128i;
-        let b_ptr = b.as_ptr() as *const __m128i;
-        let a_vec = _mm_loadu_si128(a_ptr);
-        let b_vec = _mm_loadu_si128(b_ptr);
-        let res = _mm_xor_si128(a_vec, b_vec);
-        _mm_storeu_si128(a_ptr, res);
-    }
-}
\ No newline at end of file
+    #[inline(always)]
+    fn process_block(&self, input: &[u8; 16], output: &mut [u8; 16]) {
+        let mut block = *input;
+
+        if let Some(whitening) = &self.whitening {
+            self.backend.xor_block(&mut block, whitening);
+        }
+        for round_key in self.round_keys.iter() {
+            if self.constant_time {
+                substitute_ct(&self.s_box, &mut block);
+                self.permutation.apply_bitwise(&mut block);
+            } else {
+                substitute(&self.s_box, &mut block);
+                self.permutation.apply(&mut block);
+            }
+            self.backend.xor_block(&mut block, round_key);
+        }
+
+        output.copy_from_slice(&block);
+    }
+}
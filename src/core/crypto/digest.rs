@@ -0,0 +1,65 @@
+//! Общий интерфейс хеш-функций семейства SHA-2, поверх которого строятся HMAC
+//! и производные от него
+use super::sha256::{Sha224, Sha256};
+use super::sha512::{Sha384, Sha512};
+
+/// Инкрементальная хеш-функция Меркла — Дамгора
+pub trait Digest: Clone {
+    /// Размер блока сжатия в байтах (нужен HMAC для дополнения ключа)
+    const BLOCK_LEN: usize;
+    /// Длина результата в байтах
+    const OUTPUT_LEN: usize;
+    type Output: AsRef<[u8]> + AsMut<[u8]> + Copy;
+
+    fn new() -> Self;
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self) -> Self::Output;
+
+    /// Хеш одного сообщения
+    fn digest(data: &[u8]) -> Self::Output {
+        let mut hasher = Self::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+macro_rules! impl_digest {
+    ($($t:ty => block $block:literal, output $output:literal;)*) => {$(
+        impl Digest for $t {
+            const BLOCK_LEN: usize = $block;
+            const OUTPUT_LEN: usize = $output;
+            type Output = [u8; $output];
+
+            fn new() -> Self {
+                <$t>::new()
+            }
+
+            fn update(&mut self, data: &[u8]) {
+                <$t>::update(self, data);
+            }
+
+            fn finalize(self) -> [u8; $output] {
+                <$t>::finalize(self)
+            }
+        }
+
+        /// Потоковое хеширование, например `io::copy(&mut file, &mut hasher)`
+        impl std::io::Write for $t {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                <$t>::update(self, buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+    )*};
+}
+
+impl_digest! {
+    Sha224 => block 64, output 28;
+    Sha256 => block 64, output 32;
+    Sha384 => block 128, output 48;
+    Sha512 => block 128, output 64;
+}
@@ -0,0 +1,144 @@
+//! GCM (NIST SP 800-38D) поверх 128-битного SPN: CTR-шифрование + GHASH над GF(2^128).
+//!
+//! Раскладка счётчика та же, что в `Metadata::iv`: 12 байт nonce и 4 байта счётчика.
+//! `J0 = nonce || 1` маскирует тег, данные шифруются начиная с `nonce || 2`.
+use super::cipher::{Cipher, BLOCK_SIZE};
+use super::hmac::ct_eq;
+use super::secret::{Secret, Zeroize};
+
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+/// Счётчик 32-битный, а блоки 0 и 1 заняты под H и маску тега
+pub const MAX_BLOCKS: u64 = (1 << 32) - 2;
+
+/// Неприводимый многочлен x^128 + x^7 + x^2 + x + 1 в отражённом порядке битов GCM
+const R: u128 = 0xe1 << 120;
+
+/// Умножение в GF(2^128) в битовом порядке GCM (бит 0 — старший бит первого байта).
+/// Без ветвлений по данным: маски вместо `if`
+pub fn gf_mul(x: u128, y: u128) -> u128 {
+    let mut z = 0u128;
+    let mut v = y;
+    for i in (0..128).rev() {
+        z ^= v & 0u128.wrapping_sub((x >> i) & 1);
+        v = (v >> 1) ^ (R & 0u128.wrapping_sub(v & 1));
+    }
+    z
+}
+
+/// Инкрементальный GHASH. Каждая часть (AAD, шифртекст) дополняется нулями до блока
+pub struct Ghash {
+    h: u128,
+    acc: u128,
+}
+
+impl Drop for Ghash {
+    fn drop(&mut self) {
+        self.h.zeroize();
+        self.acc.zeroize();
+    }
+}
+
+impl Ghash {
+    pub fn new(h: &[u8; BLOCK_SIZE]) -> Self {
+        Ghash { h: u128::from_be_bytes(*h), acc: 0 }
+    }
+
+    /// Поглощает `data`; неполный последний блок дополняется нулями
+    pub fn update_padded(&mut self, data: &[u8]) -> &mut Self {
+        for chunk in data.chunks(BLOCK_SIZE) {
+            let mut block = [0u8; BLOCK_SIZE];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.acc = gf_mul(self.acc ^ u128::from_be_bytes(block), self.h);
+        }
+        self
+    }
+
+    /// Завершает блоком длин `len(A) || len(C)` в битах
+    pub fn finalize(mut self, aad_len: u64, ct_len: u64) -> [u8; BLOCK_SIZE] {
+        let lengths = ((aad_len as u128 * 8) << 64) | (ct_len as u128 * 8);
+        self.acc = gf_mul(self.acc ^ lengths, self.h);
+        self.acc.to_be_bytes()
+    }
+}
+
+/// `GHASH_H(A, C)` за один вызов
+pub fn ghash(h: &[u8; BLOCK_SIZE], aad: &[u8], ciphertext: &[u8]) -> [u8; BLOCK_SIZE] {
+    let mut g = Ghash::new(h);
+    g.update_padded(aad).update_padded(ciphertext);
+    g.finalize(aad.len() as u64, ciphertext.len() as u64)
+}
+
+/// GCM с заранее вычисленным ключом хеширования `H = E(0^128)`
+pub struct Gcm<'a> {
+    cipher: &'a Cipher,
+    h: Secret<[u8; BLOCK_SIZE]>,
+}
+
+impl<'a> Gcm<'a> {
+    pub fn new(cipher: &'a Cipher) -> Self {
+        let mut h = Secret::new([0u8; BLOCK_SIZE]);
+        cipher.encrypt_block(&mut h);
+        Gcm { cipher, h }
+    }
+
+    fn counter_block(nonce: &[u8; NONCE_LEN], counter: u32) -> [u8; BLOCK_SIZE] {
+        let mut block = [0u8; BLOCK_SIZE];
+        block[..NONCE_LEN].copy_from_slice(nonce);
+        block[NONCE_LEN..].copy_from_slice(&counter.to_be_bytes());
+        block
+    }
+
+    /// Гамма начинается с `nonce || 2`. При длине не больше `MAX_BLOCKS` блоков
+    /// 64-битный счётчик `apply_ctr_at` не выходит за последние 4 байта и
+    /// совпадает с inc32 из спецификации
+    fn apply_keystream(&self, nonce: &[u8; NONCE_LEN], buf: &mut [u8]) -> Result<(), &'static str> {
+        if buf.len().div_ceil(BLOCK_SIZE) as u64 > MAX_BLOCKS {
+            return Err("GCM message too long");
+        }
+        self.cipher.apply_ctr_at(&Self::counter_block(nonce, 2), 0, buf)
+    }
+
+    fn tag(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], ciphertext: &[u8]) -> [u8; TAG_LEN] {
+        let mut tag = ghash(&self.h, aad, ciphertext);
+        let mut mask = Self::counter_block(nonce, 1);
+        self.cipher.encrypt_block(&mut mask);
+        tag.iter_mut().zip(mask.iter()).for_each(|(t, m)| *t ^= m);
+        tag
+    }
+
+    /// Шифрует `buf` на месте и возвращает тег
+    pub fn encrypt(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], buf: &mut [u8]) -> Result<[u8; TAG_LEN], &'static str> {
+        self.apply_keystream(nonce, buf)?;
+        Ok(self.tag(nonce, aad, buf))
+    }
+
+    /// Проверяет тег и только после этого расшифровывает `buf` на месте
+    pub fn decrypt(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], buf: &mut [u8], tag: &[u8]) -> Result<(), &'static str> {
+        if !ct_eq(&self.tag(nonce, aad, buf), tag) {
+            return Err("Authentication failed");
+        }
+        self.apply_keystream(nonce, buf)
+    }
+}
+
+/// Возвращает `шифртекст || тег`
+pub fn encrypt_aead(key: &[u8; 32], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let cipher = Cipher::new(*key);
+    let mut out = plaintext.to_vec();
+    let tag = Gcm::new(&cipher).encrypt(nonce, aad, &mut out)?;
+    out.extend_from_slice(&tag);
+    Ok(out)
+}
+
+/// Ожидает `шифртекст || тег`, как возвращает `encrypt_aead`
+pub fn decrypt_aead(key: &[u8; 32], nonce: &[u8; NONCE_LEN], aad: &[u8], data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if data.len() < TAG_LEN {
+        return Err("Invalid ciphertext length");
+    }
+    let (ciphertext, tag) = data.split_at(data.len() - TAG_LEN);
+    let cipher = Cipher::new(*key);
+    let mut out = ciphertext.to_vec();
+    Gcm::new(&cipher).decrypt(nonce, aad, &mut out, tag)?;
+    Ok(out)
+}
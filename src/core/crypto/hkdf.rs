@@ -0,0 +1,73 @@
+//! HKDF (RFC 5869): выработка независимых подключей из одного мастер-ключа.
+//!
+//! `extract` сжимает входной материал с солью в псевдослучайный ключ (PRK),
+//! `expand` разворачивает PRK в ключ нужной длины; разные `info` дают
+//! независимые ключи.
+use std::marker::PhantomData;
+use super::digest::Digest;
+use super::hmac::Hmac;
+use super::secret::{Secret, Zeroize};
+
+pub struct Hkdf<H: Digest> {
+    prk: Secret<Vec<u8>>,
+    _hash: PhantomData<H>,
+}
+
+impl<H: Digest> Hkdf<H> {
+    /// HKDF-Extract; пустая соль равносильна `HashLen` нулевых байт
+    pub fn extract(salt: &[u8], ikm: &[u8]) -> Self {
+        let mut prk = Hmac::<H>::mac(salt, ikm);
+        let hkdf = Hkdf { prk: Secret::new(prk.as_ref().to_vec()), _hash: PhantomData };
+        prk.as_mut().zeroize();
+        hkdf
+    }
+
+    /// Пропускает extract, если PRK уже равномерно случаен
+    pub fn from_prk(prk: &[u8]) -> Result<Self, &'static str> {
+        if prk.len() < H::OUTPUT_LEN {
+            return Err("HKDF PRK is shorter than the hash output");
+        }
+        Ok(Hkdf { prk: Secret::new(prk.to_vec()), _hash: PhantomData })
+    }
+
+    pub fn prk(&self) -> &[u8] {
+        &self.prk
+    }
+
+    /// HKDF-Expand: заполняет `okm`, не больше `255 * HashLen` байт
+    pub fn expand(&self, info: &[u8], okm: &mut [u8]) -> Result<(), &'static str> {
+        if okm.len() > 255 * H::OUTPUT_LEN {
+            return Err("HKDF output too long");
+        }
+        // T(i) = HMAC(PRK, T(i-1) || info || i), T(0) пустой
+        let mut previous: Option<H::Output> = None;
+        for (i, chunk) in okm.chunks_mut(H::OUTPUT_LEN).enumerate() {
+            let mut mac = Hmac::<H>::new(&self.prk);
+            if let Some(t) = &previous {
+                mac.update(t.as_ref());
+            }
+            mac.update(info).update(&[i as u8 + 1]);
+            let t = mac.finalize();
+            chunk.copy_from_slice(&t.as_ref()[..chunk.len()]);
+            if let Some(mut old) = previous.replace(t) {
+                old.as_mut().zeroize();
+            }
+        }
+        if let Some(mut last) = previous {
+            last.as_mut().zeroize();
+        }
+        Ok(())
+    }
+
+    /// `expand` в массив фиксированной длины
+    pub fn expand_array<const N: usize>(&self, info: &[u8]) -> Result<Secret<[u8; N]>, &'static str> {
+        let mut okm = Secret::new([0u8; N]);
+        self.expand(info, &mut okm[..])?;
+        Ok(okm)
+    }
+}
+
+/// Extract и expand за один вызов
+pub fn hkdf<H: Digest>(salt: &[u8], ikm: &[u8], info: &[u8], okm: &mut [u8]) -> Result<(), &'static str> {
+    Hkdf::<H>::extract(salt, ikm).expand(info, okm)
+}
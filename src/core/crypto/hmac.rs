@@ -0,0 +1,101 @@
+//! HMAC (RFC 2104) над любой хеш-функцией с интерфейсом `Digest`
+use super::digest::Digest;
+use super::sha256::Sha256;
+use super::sha512::{Sha384, Sha512};
+use super::secret::Zeroize;
+
+/// Наибольший размер блока среди поддерживаемых хешей
+const MAX_BLOCK_LEN: usize = 128;
+pub const TAG_LEN: usize = 32;
+
+/// HMAC-SHA256, которым подписываются зашифрованные файлы
+pub type HmacSha256 = Hmac<Sha256>;
+pub type HmacSha384 = Hmac<Sha384>;
+pub type HmacSha512 = Hmac<Sha512>;
+
+#[derive(Clone)]
+pub struct Hmac<H: Digest> {
+    inner: H,
+    outer: H,
+}
+
+impl<H: Digest> Hmac<H> {
+    pub fn new(key: &[u8]) -> Self {
+        let block_len = H::BLOCK_LEN;
+        let mut block = [0u8; MAX_BLOCK_LEN];
+        if key.len() > block_len {
+            let mut hasher = H::new();
+            hasher.update(key);
+            let mut hashed = hasher.finalize();
+            block[..H::OUTPUT_LEN].copy_from_slice(hashed.as_ref());
+            hashed.as_mut().zeroize();
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+
+        let mut ipad = [0x36u8; MAX_BLOCK_LEN];
+        let mut opad = [0x5cu8; MAX_BLOCK_LEN];
+        for ((i, o), k) in ipad.iter_mut().zip(opad.iter_mut()).zip(block.iter()) {
+            *i ^= k;
+            *o ^= k;
+        }
+
+        let mut inner = H::new();
+        inner.update(&ipad[..block_len]);
+        let mut outer = H::new();
+        outer.update(&opad[..block_len]);
+        block.zeroize();
+        ipad.zeroize();
+        opad.zeroize();
+        Hmac { inner, outer }
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.inner.update(data);
+        self
+    }
+
+    pub fn finalize(self) -> H::Output {
+        let inner_hash = self.inner.finalize();
+        let mut outer = self.outer;
+        outer.update(inner_hash.as_ref());
+        outer.finalize()
+    }
+
+    /// Сравнивает тег с ожидаемым за постоянное время. Допускается тег,
+    /// усечённый до левых `tag.len()` байт, но не короче половины выхода
+    /// (RFC 2104, раздел 5) и не короче 16 байт
+    pub fn verify(self, tag: &[u8]) -> bool {
+        let min_len = (H::OUTPUT_LEN / 2).max(16);
+        let expected = self.finalize();
+        tag.len() >= min_len && ct_eq(&expected.as_ref()[..tag.len().min(H::OUTPUT_LEN)], tag)
+    }
+
+    /// Тег одного сообщения
+    pub fn mac(key: &[u8], data: &[u8]) -> H::Output {
+        let mut mac = Self::new(key);
+        mac.update(data);
+        mac.finalize()
+    }
+}
+
+/// Позволяет подать в MAC поток через `io::copy`
+impl<H: Digest> std::io::Write for Hmac<H> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Compares two byte strings without an early exit on the first mismatch
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    diff == 0
+}
@@ -1,4 +1,6 @@
 use crate::core::crypto::sha256::Sha256;
+use crate::core::crypto::secret::Secret;
+use crate::core::crypto::scrypt::{scrypt, ScryptParams};
 use std::f64::consts::PI;
 
 // Конфигурируемые параметры
@@ -41,18 +43,47 @@ impl ReflectionSide {
     }
 }
 
+/// Несолёный вариант, оставлен для файлов, созданных до появления соли
 pub fn derive_key(password: &[u8]) -> [u8; 32] {
-    let reflection_sequence = simulate_billiard(password);
+    let hash = Secret::new(initial_hash(password));
+    let reflection_sequence = simulate_billiard(&hash);
     let mut hasher = Sha256::new();
     hasher.update(&reflection_sequence);
     hasher.finalize()
 }
 
-/// Симулирует движение бильярдного шара для генерации последовательности отражений
-fn simulate_billiard(password: &[u8]) -> Vec<u8> {
-    let hash = initial_hash(password);
-    let (x, y, angle) = parse_hash(&hash);
-    let mut reflection_sequence = Vec::with_capacity(REFLECTIONS);
+/// Выводит ключ из пароля и соли: соль подмешивается в начальное состояние шара,
+/// поэтому одинаковые пароли с разной солью дают разные траектории
+pub fn derive_key_salted(password: &[u8], salt: &[u8]) -> [u8; 32] {
+    let hash = Secret::new(salted_hash(password, salt));
+    let reflection_sequence = simulate_billiard(&hash);
+    // Начальный хеш сохраняет все 256 бит пароля и соли, которые теряются при переводе в f64
+    let mut hasher = Sha256::new();
+    hasher.update(&hash[..]);
+    hasher.update(&reflection_sequence);
+    hasher.finalize()
+}
+
+/// Ключ scrypt. С `billiard_prehash` на вход scrypt подаётся результат
+/// `derive_key_salted`, а не сам пароль: перебор требует и бильярда, и памяти scrypt
+pub fn derive_key_scrypt(
+    password: &[u8],
+    salt: &[u8],
+    params: &ScryptParams,
+    billiard_prehash: bool,
+) -> Result<[u8; 32], &'static str> {
+    let prehash = billiard_prehash.then(|| Secret::new(derive_key_salted(password, salt)));
+    let input = prehash.as_ref().map_or(password, |hash| &hash[..]);
+    let mut key = [0u8; 32];
+    scrypt(input, salt, params, &mut key)?;
+    Ok(key)
+}
+
+/// Симулирует движение бильярдного шара для генерации последовательности отражений.
+/// Последовательность однозначно определяет ключ, поэтому затирается после использования
+fn simulate_billiard(hash: &[u8; 32]) -> Secret<Vec<u8>> {
+    let (x, y, angle) = parse_hash(hash);
+    let mut reflection_sequence = Secret::new(Vec::with_capacity(REFLECTIONS));
 
     let mut pos = Position { x, y };
     let mut dir = Direction {
@@ -88,6 +119,15 @@ fn initial_hash(password: &[u8]) -> [u8; 32] {
     hasher.finalize()
 }
 
+/// Начальный хеш с солью; длина соли входит в хеш, чтобы границу соли и пароля нельзя было сдвинуть
+fn salted_hash(password: &[u8], salt: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(&(salt.len() as u64).to_be_bytes());
+    hasher.update(salt);
+    hasher.update(password);
+    hasher.finalize()
+}
+
 /// Преобразует хеш в начальные параметры шара
 fn parse_hash(hash: &[u8; 32]) -> (f64, f64, f64) {
     let x = to_normalized_f64(&hash[0..8]);
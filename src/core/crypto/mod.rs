@@ -1,5 +1,17 @@
 pub mod keygen;
 pub mod sha256;
+pub mod sha512;
 pub mod cipher;
 pub mod s_box;
 pub mod p_box;
+pub mod hmac;
+pub mod modes;
+pub mod permutation;
+pub mod backend;
+pub mod gcm;
+pub mod siv;
+pub mod secret;
+pub mod digest;
+pub mod hkdf;
+pub mod pbkdf2;
+pub mod scrypt;
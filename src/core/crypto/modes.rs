@@ -0,0 +1,250 @@
+//! Блочные режимы поверх `Cipher::encrypt_block`/`decrypt_block`
+use rayon::prelude::*;
+use super::cipher::{Cipher, CtrStream, BLOCK_SIZE};
+
+/// Общий интерфейс режимов. Состояние переносится между вызовами, поэтому
+/// данные можно подавать буферами; все буферы, кроме последнего, должны быть
+/// кратны размеру блока
+pub trait CipherMode {
+    /// Режим работает только с целыми блоками и требует дополнения PKCS#7
+    fn padded(&self) -> bool {
+        false
+    }
+
+    fn encrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str>;
+
+    fn decrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str>;
+}
+
+impl CipherMode for CtrStream<'_> {
+    fn encrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        self.apply(buf)
+    }
+
+    fn decrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        self.apply(buf)
+    }
+}
+
+/// Дополняет данные по PKCS#7 до кратного размеру блока (всегда от 1 до 16 байт)
+pub fn pkcs7_pad(data: &mut Vec<u8>) {
+    let pad = BLOCK_SIZE - data.len() % BLOCK_SIZE;
+    data.resize(data.len() + pad, pad as u8);
+}
+
+/// Возвращает длину данных без дополнения PKCS#7
+pub fn pkcs7_unpad(data: &[u8]) -> Result<usize, &'static str> {
+    if data.is_empty() || !data.len().is_multiple_of(BLOCK_SIZE) {
+        return Err("Invalid PKCS#7 padding: length is not a multiple of the block size");
+    }
+    let pad = data[data.len() - 1] as usize;
+    if pad == 0 || pad > BLOCK_SIZE || data[data.len() - pad..].iter().any(|&b| b as usize != pad) {
+        return Err("Invalid PKCS#7 padding");
+    }
+    Ok(data.len() - pad)
+}
+
+#[inline(always)]
+fn xor_in_place(dst: &mut [u8], src: &[u8]) {
+    dst.iter_mut().zip(src.iter()).for_each(|(d, s)| *d ^= s);
+}
+
+/// Расшифровывает блоки параллельно, когда блок `i` зависит только от шифрблока `i - 1`
+/// (CBC и CFB). `prev` — шифрблок перед началом буфера; на выходе — последний шифрблок
+fn par_chain_decrypt(
+    buf: &mut [u8],
+    prev: &mut [u8; BLOCK_SIZE],
+    f: impl Fn(&mut [u8], &[u8]) + Sync,
+) {
+    if buf.is_empty() {
+        return;
+    }
+    let ciphertext = buf.to_vec();
+    let first = *prev;
+    buf.par_chunks_mut(BLOCK_SIZE)
+        .enumerate()
+        .for_each(|(i, chunk)| {
+            let prev = if i == 0 { &first[..] } else { &ciphertext[(i - 1) * BLOCK_SIZE..i * BLOCK_SIZE] };
+            f(chunk, prev);
+        });
+    // После неполного блока продолжать цепочку нельзя, он всегда последний
+    if ciphertext.len().is_multiple_of(BLOCK_SIZE) {
+        prev.copy_from_slice(&ciphertext[ciphertext.len() - BLOCK_SIZE..]);
+    }
+}
+
+/// CBC: `C_i = E(P_i ^ C_{i-1})`, только целые блоки
+pub struct Cbc<'a> {
+    cipher: &'a Cipher,
+    prev: [u8; BLOCK_SIZE],
+}
+
+impl<'a> Cbc<'a> {
+    pub fn new(cipher: &'a Cipher, iv: [u8; BLOCK_SIZE]) -> Self {
+        Cbc { cipher, prev: iv }
+    }
+}
+
+impl CipherMode for Cbc<'_> {
+    fn padded(&self) -> bool {
+        true
+    }
+
+    /// Цепочка по природе последовательна
+    fn encrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        if !buf.len().is_multiple_of(BLOCK_SIZE) {
+            return Err("CBC input must be a multiple of the block size");
+        }
+        for chunk in buf.chunks_exact_mut(BLOCK_SIZE) {
+            let block: &mut [u8; BLOCK_SIZE] = chunk.try_into().unwrap();
+            xor_in_place(block, &self.prev);
+            self.cipher.encrypt_block(block);
+            self.prev = *block;
+        }
+        Ok(())
+    }
+
+    fn decrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        if !buf.len().is_multiple_of(BLOCK_SIZE) {
+            return Err("Invalid ciphertext length");
+        }
+        let cipher = self.cipher;
+        par_chain_decrypt(buf, &mut self.prev, |chunk, prev| {
+            let block: &mut [u8; BLOCK_SIZE] = chunk.try_into().unwrap();
+            cipher.decrypt_block(block);
+            xor_in_place(block, prev);
+        });
+        Ok(())
+    }
+}
+
+/// CFB с полным блоком обратной связи: `C_i = P_i ^ E(C_{i-1})`
+pub struct Cfb<'a> {
+    cipher: &'a Cipher,
+    prev: [u8; BLOCK_SIZE],
+}
+
+impl<'a> Cfb<'a> {
+    pub fn new(cipher: &'a Cipher, iv: [u8; BLOCK_SIZE]) -> Self {
+        Cfb { cipher, prev: iv }
+    }
+}
+
+impl CipherMode for Cfb<'_> {
+    fn encrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        for chunk in buf.chunks_mut(BLOCK_SIZE) {
+            let mut key_stream = self.prev;
+            self.cipher.encrypt_block(&mut key_stream);
+            xor_in_place(chunk, &key_stream);
+            if chunk.len() == BLOCK_SIZE {
+                self.prev.copy_from_slice(chunk);
+            }
+        }
+        Ok(())
+    }
+
+    /// Гамма зависит только от шифртекста, поэтому блоки расшифровываются параллельно
+    fn decrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        let cipher = self.cipher;
+        par_chain_decrypt(buf, &mut self.prev, |chunk, prev| {
+            let mut key_stream: [u8; BLOCK_SIZE] = prev.try_into().unwrap();
+            cipher.encrypt_block(&mut key_stream);
+            xor_in_place(chunk, &key_stream);
+        });
+        Ok(())
+    }
+}
+
+/// CFB-8: регистр сдвигается на один байт шифртекста, один вызов шифра на байт
+pub struct Cfb8<'a> {
+    cipher: &'a Cipher,
+    register: [u8; BLOCK_SIZE],
+}
+
+impl<'a> Cfb8<'a> {
+    pub fn new(cipher: &'a Cipher, iv: [u8; BLOCK_SIZE]) -> Self {
+        Cfb8 { cipher, register: iv }
+    }
+
+    #[inline(always)]
+    fn next_key_byte(&self) -> u8 {
+        let mut block = self.register;
+        self.cipher.encrypt_block(&mut block);
+        block[0]
+    }
+
+    #[inline(always)]
+    fn shift_in(&mut self, byte: u8) {
+        self.register.copy_within(1.., 0);
+        self.register[BLOCK_SIZE - 1] = byte;
+    }
+}
+
+impl CipherMode for Cfb8<'_> {
+    fn encrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        for byte in buf.iter_mut() {
+            *byte ^= self.next_key_byte();
+            self.shift_in(*byte);
+        }
+        Ok(())
+    }
+
+    fn decrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        for byte in buf.iter_mut() {
+            let ciphertext = *byte;
+            *byte ^= self.next_key_byte();
+            self.shift_in(ciphertext);
+        }
+        Ok(())
+    }
+}
+
+/// OFB: гамма `O_i = E(O_{i-1})` не зависит от данных; шифрование и расшифрование совпадают
+pub struct Ofb<'a> {
+    cipher: &'a Cipher,
+    state: [u8; BLOCK_SIZE],
+}
+
+impl<'a> Ofb<'a> {
+    pub fn new(cipher: &'a Cipher, iv: [u8; BLOCK_SIZE]) -> Self {
+        Ofb { cipher, state: iv }
+    }
+
+    fn apply(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(BLOCK_SIZE) {
+            self.cipher.encrypt_block(&mut self.state);
+            xor_in_place(chunk, &self.state);
+        }
+    }
+}
+
+impl CipherMode for Ofb<'_> {
+    fn encrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        self.apply(buf);
+        Ok(())
+    }
+
+    fn decrypt(&mut self, buf: &mut [u8]) -> Result<(), &'static str> {
+        self.apply(buf);
+        Ok(())
+    }
+}
+
+/// CBC с дополнением PKCS#7; IV в результат не входит
+pub fn encrypt_cbc(cipher: &Cipher, iv: &[u8; BLOCK_SIZE], data: &[u8]) -> Vec<u8> {
+    let mut out = data.to_vec();
+    pkcs7_pad(&mut out);
+    Cbc::new(cipher, *iv).encrypt(&mut out).expect("padded input is block aligned");
+    out
+}
+
+pub fn decrypt_cbc(cipher: &Cipher, iv: &[u8; BLOCK_SIZE], data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if data.is_empty() || !data.len().is_multiple_of(BLOCK_SIZE) {
+        return Err("Invalid ciphertext length");
+    }
+    let mut out = data.to_vec();
+    Cbc::new(cipher, *iv).decrypt(&mut out)?;
+    let len = pkcs7_unpad(&out)?;
+    out.truncate(len);
+    Ok(out)
+}
@@ -4,12 +4,28 @@ pub const P_BOX: [usize; 128] = [
     127,126,125,124,123,122,121,120,119,118,117,116,115,114,113,112,111,110,109,108,107,106,105,104,103,102,101,100,99,98,97,96,95,94,93,92,91,90,89,88,87,86,85,84,83,82,81,80,79,78,77,76,75,74,73,72,71,70,69,68,67,66,65,64,63,62,61,60,59,58,57,56,55,54,53,52,51,50,49,48,47,46,45,44,43,42,41,40,39,38,37,36,35,34,33,32,31,30,29,28,27,26,25,24,23,22,21,20,19,18,17,16,15,14,13,12,11,10,9,8,7,6,5,4,3,2,1,0
 ];
 
-pub const INV_P_BOX: [usize; 128] = {
+/// Транспонирующая перестановка: бит `j` байта `b` переходит в позицию `16 * j + b`,
+/// так что восемь выходных битов одного S-box расходятся по восьми разным байтам
+/// (как P-слой PRESENT). Разворот битов `P_BOX` такой диффузии не даёт
+pub const TRANSPOSE_P_BOX: [usize; 128] = {
+    let mut p = [0; 128];
+    let mut i = 0;
+    while i < 128 {
+        p[i] = (i % 8) * 16 + i / 8;
+        i += 1;
+    }
+    p
+};
+
+/// Обратная перестановка
+pub const fn invert(p_box: &[usize; 128]) -> [usize; 128] {
     let mut inv = [0; 128];
     let mut i = 0;
     while i < 128 {
-        inv[P_BOX[i]] = i;
+        inv[p_box[i]] = i;
         i += 1;
     }
     inv
-};
\ No newline at end of file
+}
+
+pub const INV_P_BOX: [usize; 128] = invert(&P_BOX);
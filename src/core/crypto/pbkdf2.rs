@@ -0,0 +1,42 @@
+//! PBKDF2 (RFC 8018) над HMAC с любым `Digest` — стандартная альтернатива
+//! бильярдному KDF из `keygen`.
+use super::digest::Digest;
+use super::hmac::Hmac;
+use super::secret::Zeroize;
+
+/// Число итераций по умолчанию для PBKDF2-HMAC-SHA256 (рекомендация OWASP)
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+/// Предел итераций: заголовок не может заставить расшифрование работать минутами
+pub const MAX_ITERATIONS: u32 = 10_000_000;
+
+/// Заполняет `out` ключом, выведенным из пароля и соли
+pub fn pbkdf2<H: Digest>(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) -> Result<(), &'static str> {
+    if iterations == 0 {
+        return Err("PBKDF2 iteration count must be positive");
+    }
+    if out.len() as u64 > u32::MAX as u64 * H::OUTPUT_LEN as u64 {
+        return Err("PBKDF2 output too long");
+    }
+    // Ключ HMAC один на все вызовы: копируем уже подготовленное состояние
+    let prf = Hmac::<H>::new(password);
+    for (i, chunk) in out.chunks_mut(H::OUTPUT_LEN).enumerate() {
+        // T_i = U_1 ^ ... ^ U_c, U_1 = PRF(S || INT(i)), U_j = PRF(U_{j-1})
+        let mut mac = prf.clone();
+        mac.update(salt).update(&(i as u32 + 1).to_be_bytes());
+        let mut u = mac.finalize();
+        let mut t = u;
+        for _ in 1..iterations {
+            let mut mac = prf.clone();
+            mac.update(u.as_ref());
+            let next = mac.finalize();
+            u.as_mut().zeroize();
+            u = next;
+            t.as_mut().iter_mut().zip(u.as_ref()).for_each(|(t, u)| *t ^= u);
+        }
+        chunk.copy_from_slice(&t.as_ref()[..chunk.len()]);
+        u.as_mut().zeroize();
+        t.as_mut().zeroize();
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! Слой перестановки битов SPN.
+//!
+//! Перестановка линейна над GF(2), поэтому результат — это OR вкладов каждого
+//! входного байта: `P(x) = T[0][x0] | T[1][x1] | ... | T[15][x15]`. Таблица `T`
+//! (16×256 масок u128, 64 КиБ) строится один раз для P-box, и блок переставляется
+//! за 16 обращений к памяти вместо 128 побитовых операций.
+use super::p_box::invert;
+
+#[derive(Copy, Clone)] // Добавлены трейты Copy и Clone
+struct PermutationInfo {
+    old_byte: usize,
+    old_bit: u8,
+}
+
+type PermutationTable = [[PermutationInfo; 8]; 16];
+
+/// Меняет нумерацию битов внутри байта на обратную (MSB-first <-> LSB-first)
+const fn flip(pos: usize) -> usize {
+    pos ^ 7
+}
+
+/// Строит таблицу перестановки: выходной бит `pos` (LSB-first) берётся из входного
+/// бита `p_box[pos]` (MSB-first). Для обратной таблицы из-за разной нумерации
+/// входа и выхода индексы `inv_p_box` зеркалируются с обеих сторон
+const fn build_table(p_box: &[usize; 128], inverse: bool) -> PermutationTable {
+    let mut table = [[PermutationInfo { old_byte: 0, old_bit: 0 }; 8]; 16];
+
+    // Вычисление значений для каждого элемента в константном контексте
+    let mut new_byte = 0;
+    while new_byte < 16 {
+        let mut new_bit = 0;
+        while new_bit < 8 {
+            let pos = new_byte * 8 + (7 - new_bit);
+            let old_pos = if inverse { flip(p_box[flip(pos)]) } else { p_box[pos] };
+            table[new_byte][new_bit] = PermutationInfo {
+                old_byte: old_pos / 8,
+                old_bit: (7 - (old_pos % 8)) as u8,
+            };
+            new_bit += 1;
+        }
+        new_byte += 1;
+    }
+    table
+}
+
+#[inline(always)]
+fn permute_bits(block: &mut [u8; 16], table: &PermutationTable) {
+    let mut new_block = [0u8; 16];
+    for (new_byte, bits) in table.iter().enumerate() {
+        new_block[new_byte] = bits.iter().enumerate()
+            .fold(0u8, |acc, (new_bit, info)| {
+                let bit = (block[info.old_byte] >> info.old_bit) & 1;
+                acc | (bit << (7 - new_bit))
+            });
+    }
+    *block = new_block;
+}
+
+/// Перестановка битов блока с побайтовыми таблицами вкладов
+pub struct Permutation {
+    bits: PermutationTable,
+    bytes: Box<[[u128; 256]; 16]>,
+}
+
+impl Permutation {
+    /// Прямая перестановка для `p_box`
+    pub fn forward(p_box: &[usize; 128]) -> Self {
+        Self::from_table(build_table(p_box, false))
+    }
+
+    /// Обратная к `forward(p_box)`
+    pub fn inverse(p_box: &[usize; 128]) -> Self {
+        Self::from_table(build_table(&invert(p_box), true))
+    }
+
+    fn from_table(bits: PermutationTable) -> Self {
+        let mut bytes = Box::new([[0u128; 256]; 16]);
+        for (byte, row) in bytes.iter_mut().enumerate() {
+            // Вклад каждого отдельного бита, остальные значения — OR вкладов битов
+            for bit in 0..8 {
+                let mut block = [0u8; 16];
+                block[byte] = 1 << bit;
+                permute_bits(&mut block, &bits);
+                row[1 << bit] = u128::from_be_bytes(block);
+            }
+            for value in 1..256usize {
+                let low = value & value.wrapping_neg();
+                row[value] = row[low] | row[value ^ low];
+            }
+        }
+        Permutation { bits, bytes }
+    }
+
+    #[inline(always)]
+    pub fn apply(&self, block: &mut [u8; 16]) {
+        let out = block.iter()
+            .zip(self.bytes.iter())
+            .fold(0u128, |acc, (&b, row)| acc | row[b as usize]);
+        *block = out.to_be_bytes();
+    }
+
+    /// Исходная побитовая перестановка; медленнее, но служит эталоном для `apply`.
+    /// Адреса чтения зависят только от P-box, а не от блока, поэтому это и
+    /// вариант для режима постоянного времени
+    pub fn apply_bitwise(&self, block: &mut [u8; 16]) {
+        permute_bits(block, &self.bits);
+    }
+}
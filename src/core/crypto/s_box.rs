@@ -1,4 +1,6 @@
 //! S-box implementation (AES S-box example)
+use super::sha256::Sha256;
+
 pub const S_BOX: [u8; 256] = [
     0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76, 
     0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0, 
@@ -37,4 +39,129 @@ pub const INV_S_BOX: [u8; 256] = [
         0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
         0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d
 
-];
\ No newline at end of file
+];
+
+/// Подстановка табличным поиском; адрес обращения зависит от данных
+#[inline(always)]
+pub fn substitute(s_box: &[u8; 256], block: &mut [u8; 16]) {
+    block.iter_mut().for_each(|byte| *byte = s_box[*byte as usize]);
+}
+
+/// Подстановка за постоянное время: для каждого байта читается вся таблица,
+/// нужное значение выбирается маской, поэтому последовательность обращений к
+/// памяти и ветвлений не зависит от данных. Результат совпадает с `substitute`
+pub fn substitute_ct(s_box: &[u8; 256], block: &mut [u8; 16]) {
+    let mut out = [0u8; 16];
+    for (i, &value) in s_box.iter().enumerate() {
+        for (o, &b) in out.iter_mut().zip(block.iter()) {
+            // 0xff, если b == i, иначе 0: (d - 1) >> 8 даёт единицы только при d == 0
+            let d = (b ^ i as u8) as u32;
+            let mask = (d.wrapping_sub(1) >> 8) as u8;
+            *o |= value & mask;
+        }
+    }
+    *block = std::hint::black_box(out);
+}
+/// Порог отбора генерируемых S-box по разностной равномерности (у AES — 4,
+/// у случайной перестановки обычно 10–12)
+pub const MAX_DIFFERENTIAL_UNIFORMITY: usize = 10;
+/// Порог отбора по нелинейности (у AES — 112, у случайной перестановки обычно 90–96)
+pub const MIN_NONLINEARITY: u32 = 94;
+
+/// Максимум таблицы разностей: `max #{x : S(x) ^ S(x ^ a) = b}` по `a != 0`.
+/// Чем меньше, тем хуже работает дифференциальный криптоанализ
+pub fn differential_uniformity(s_box: &[u8; 256]) -> usize {
+    let mut worst = 0;
+    for a in 1..256 {
+        let mut counts = [0usize; 256];
+        for x in 0..256 {
+            counts[(s_box[x] ^ s_box[x ^ a]) as usize] += 1;
+        }
+        worst = worst.max(*counts.iter().max().unwrap());
+    }
+    worst
+}
+
+/// Нелинейность: расстояние от ближайшей аффинной функции по всем ненулевым
+/// линейным комбинациям выходных битов, `128 - max|W| / 2` (W — спектр Уолша)
+pub fn nonlinearity(s_box: &[u8; 256]) -> u32 {
+    let mut max_walsh = 0;
+    for mask in 1..=255u8 {
+        let mut spectrum = [0i32; 256];
+        for (x, w) in spectrum.iter_mut().enumerate() {
+            *w = 1 - 2 * ((s_box[x] & mask).count_ones() as i32 & 1);
+        }
+        // Быстрое преобразование Уолша–Адамара
+        let mut h = 1;
+        while h < 256 {
+            for i in (0..256).step_by(2 * h) {
+                for j in i..i + h {
+                    let (a, b) = (spectrum[j], spectrum[j + h]);
+                    spectrum[j] = a + b;
+                    spectrum[j + h] = a - b;
+                }
+            }
+            h *= 2;
+        }
+        max_walsh = max_walsh.max(spectrum.iter().map(|w| w.unsigned_abs()).max().unwrap());
+    }
+    128 - max_walsh / 2
+}
+
+/// Поток байтов `SHA256("spn s-box" || key || attempt || counter)`
+struct KeyStream<'a> {
+    key: &'a [u8; 32],
+    attempt: u32,
+    counter: u32,
+    block: [u8; 32],
+    pos: usize,
+}
+
+impl KeyStream<'_> {
+    fn next_byte(&mut self) -> u8 {
+        if self.pos == self.block.len() {
+            let mut hasher = Sha256::new();
+            hasher.update(b"spn s-box")
+                .update(self.key)
+                .update(&self.attempt.to_be_bytes())
+                .update(&self.counter.to_be_bytes());
+            self.block = hasher.finalize();
+            self.counter += 1;
+            self.pos = 0;
+        }
+        self.pos += 1;
+        self.block[self.pos - 1]
+    }
+
+    /// Равномерное число из `0..n` без смещения по модулю (отбрасывание хвоста)
+    fn below(&mut self, n: usize) -> usize {
+        let limit = 256 - 256 % n;
+        loop {
+            let byte = self.next_byte() as usize;
+            if byte < limit {
+                return byte % n;
+            }
+        }
+    }
+}
+
+/// Биективный S-box, зависящий только от ключа: тасование Фишера–Йетса по
+/// гамме из SHA-256. Кандидаты с плохой разностной равномерностью или
+/// нелинейностью отбрасываются, следующая попытка берёт новую гамму
+pub fn generate_s_box(key: &[u8; 32]) -> [u8; 256] {
+    for attempt in 0.. {
+        let mut stream = KeyStream { key, attempt, counter: 0, block: [0; 32], pos: 32 };
+        let mut s_box: [u8; 256] = core::array::from_fn(|i| i as u8);
+        for i in (1..256).rev() {
+            let j = stream.below(i + 1);
+            s_box.swap(i, j);
+        }
+
+        if differential_uniformity(&s_box) <= MAX_DIFFERENTIAL_UNIFORMITY
+            && nonlinearity(&s_box) >= MIN_NONLINEARITY
+        {
+            return s_box;
+        }
+    }
+    unreachable!("attempt counter exhausted")
+}
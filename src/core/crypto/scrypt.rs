@@ -0,0 +1,154 @@
+//! scrypt (RFC 7914): KDF с большим расходом памяти поверх PBKDF2-HMAC-SHA256
+//! и Salsa20/8.
+//!
+//! Каждая из `p` независимых частей (ROMix) держит в памяти `128 * r * N` байт,
+//! поэтому перебор паролей на GPU упирается в память, а не в вычисления.
+use rayon::prelude::*;
+use super::pbkdf2::pbkdf2;
+use super::sha256::Sha256;
+use super::secret::Secret;
+
+/// Предел памяти: таблица ROMix и `p` рабочих блоков, `128 * r * (N + p)` байт.
+/// Параметры из заголовка не могут потребовать больше
+pub const MAX_MEMORY: u64 = 1 << 30;
+
+/// Предел работы: суммарный объём таблиц всех `p` ROMix, `128 * r * N * p` байт
+pub const MAX_WORK: u64 = 1 << 32;
+
+/// Предел параллелизма `p`
+pub const MAX_P: u32 = 16;
+
+/// Параметры scrypt; N хранится как `log2 N`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScryptParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl ScryptParams {
+    /// N = 2^17, r = 8, p = 1 — 128 МиБ (рекомендация OWASP)
+    pub const DEFAULT: Self = ScryptParams { log_n: 17, r: 8, p: 1 };
+
+    pub fn new(log_n: u8, r: u32, p: u32) -> Result<Self, &'static str> {
+        if r == 0 || p == 0 {
+            return Err("scrypt r and p must be positive");
+        }
+        // RFC 7914: 1 < N < 2^(128 * r / 8), r * p < 2^30
+        if log_n == 0 || log_n as u64 >= 16 * r as u64 || log_n >= 64 {
+            return Err("scrypt N must be a power of two between 2 and 2^(16 * r)");
+        }
+        if r as u64 * p as u64 >= 1 << 30 {
+            return Err("scrypt r * p must be less than 2^30");
+        }
+        if p > MAX_P {
+            return Err("scrypt p is too large");
+        }
+        let block = 128 * r as u128;
+        let n = 1u128 << log_n;
+        if block * (n + p as u128) > MAX_MEMORY as u128 {
+            return Err("scrypt parameters need too much memory");
+        }
+        if block * n * p as u128 > MAX_WORK as u128 {
+            return Err("scrypt parameters need too much work");
+        }
+        Ok(ScryptParams { log_n, r, p })
+    }
+
+    pub fn n(&self) -> u64 {
+        1 << self.log_n
+    }
+
+    /// Память одного ROMix в байтах
+    pub fn memory(&self) -> u64 {
+        128 * self.r as u64 * self.n()
+    }
+}
+
+impl Default for ScryptParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Заполняет `out` ключом, выведенным из пароля и соли
+pub fn scrypt(password: &[u8], salt: &[u8], params: &ScryptParams, out: &mut [u8]) -> Result<(), &'static str> {
+    let params = ScryptParams::new(params.log_n, params.r, params.p)?;
+    let block_len = 128 * params.r as usize;
+    let mut blocks = Secret::new(vec![0u8; block_len * params.p as usize]);
+    pbkdf2::<Sha256>(password, salt, 1, &mut blocks[..])?;
+    // Части независимы и считаются параллельно
+    blocks.par_chunks_mut(block_len)
+        .for_each(|block| ro_mix(block, params.n() as usize, params.r as usize));
+    pbkdf2::<Sha256>(password, &blocks, 1, out)
+}
+
+/// ROMix: заполняет таблицу V последовательными BlockMix и читает её по индексам,
+/// зависящим от данных
+fn ro_mix(block: &mut [u8], n: usize, r: usize) {
+    let words = 32 * r;
+    let mut x = Secret::new(block.chunks_exact(4)
+        .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+        .collect::<Vec<u32>>());
+    let mut v = Secret::new(vec![0u32; words * n]);
+    let mut scratch = Secret::new(vec![0u32; words]);
+
+    for chunk in v.chunks_exact_mut(words) {
+        chunk.copy_from_slice(&x);
+        block_mix(&mut x, &mut scratch, r);
+    }
+    for _ in 0..n {
+        let j = integerify(&x, r) as usize & (n - 1);
+        x.iter_mut().zip(&v[j * words..(j + 1) * words]).for_each(|(x, v)| *x ^= v);
+        block_mix(&mut x, &mut scratch, r);
+    }
+
+    for (out, w) in block.chunks_exact_mut(4).zip(x.iter()) {
+        out.copy_from_slice(&w.to_le_bytes());
+    }
+}
+
+/// Младшие 64 бита последнего 64-байтного блока (little-endian)
+fn integerify(x: &[u32], r: usize) -> u64 {
+    let last = (2 * r - 1) * 16;
+    x[last] as u64 | (x[last + 1] as u64) << 32
+}
+
+/// BlockMix: Salsa20/8 по цепочке из `2r` блоков; чётные результаты идут в первую
+/// половину, нечётные — во вторую
+fn block_mix(b: &mut [u32], y: &mut [u32], r: usize) {
+    let mut t: [u32; 16] = b[(2 * r - 1) * 16..].try_into().unwrap();
+    for (i, chunk) in b.chunks_exact(16).enumerate() {
+        t.iter_mut().zip(chunk).for_each(|(t, c)| *t ^= c);
+        salsa20_8(&mut t);
+        let dst = (i / 2 + (i % 2) * r) * 16;
+        y[dst..dst + 16].copy_from_slice(&t);
+    }
+    b.copy_from_slice(y);
+}
+
+#[inline(always)]
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+    x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+    x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+    x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+}
+
+/// Ядро Salsa20 с 8 раундами
+fn salsa20_8(b: &mut [u32; 16]) {
+    let mut x = *b;
+    for _ in 0..4 {
+        // Столбцы
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 5, 9, 13, 1);
+        quarter_round(&mut x, 10, 14, 2, 6);
+        quarter_round(&mut x, 15, 3, 7, 11);
+        // Строки
+        quarter_round(&mut x, 0, 1, 2, 3);
+        quarter_round(&mut x, 5, 6, 7, 4);
+        quarter_round(&mut x, 10, 11, 8, 9);
+        quarter_round(&mut x, 15, 12, 13, 14);
+    }
+    b.iter_mut().zip(x).for_each(|(b, x)| *b = b.wrapping_add(x));
+}
@@ -0,0 +1,109 @@
+//! Затирание секретных данных: ключей, паролей и открытого текста.
+//!
+//! Запись нулей через `write_volatile` с барьером компилятора нельзя выбросить
+//! как «мёртвую» запись перед освобождением памяти. `Secret<T>` делает это
+//! автоматически при `drop`.
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::sync::atomic::{compiler_fence, Ordering};
+
+/// Значение, которое можно надёжно обнулить
+pub trait Zeroize {
+    fn zeroize(&mut self);
+}
+
+macro_rules! impl_zeroize_int {
+    ($($t:ty),*) => {$(
+        impl Zeroize for $t {
+            fn zeroize(&mut self) {
+                unsafe { ptr::write_volatile(self, 0) };
+                compiler_fence(Ordering::SeqCst);
+            }
+        }
+    )*};
+}
+
+impl_zeroize_int!(u8, u32, u64, u128, usize);
+
+impl<T: Zeroize> Zeroize for [T] {
+    fn zeroize(&mut self) {
+        self.iter_mut().for_each(Zeroize::zeroize);
+    }
+}
+
+impl<T: Zeroize, const N: usize> Zeroize for [T; N] {
+    fn zeroize(&mut self) {
+        self.as_mut_slice().zeroize();
+    }
+}
+
+/// Обнуляет и элементы, и свободную ёмкость (после `truncate` там остаются старые данные)
+impl<T: Zeroize> Zeroize for Vec<T> {
+    fn zeroize(&mut self) {
+        self.as_mut_slice().zeroize();
+        self.clear();
+        for slot in self.spare_capacity_mut() {
+            unsafe { ptr::write_volatile(slot, MaybeUninit::zeroed()) };
+        }
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+impl Zeroize for String {
+    fn zeroize(&mut self) {
+        // Нулевые байты — корректный UTF-8
+        unsafe { self.as_mut_vec() }.zeroize();
+    }
+}
+
+impl<T: Zeroize> Zeroize for Option<T> {
+    fn zeroize(&mut self) {
+        if let Some(value) = self {
+            value.zeroize();
+        }
+    }
+}
+
+/// Владеющая обёртка, обнуляющая значение при освобождении. Доступ — через `Deref`
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T: Zeroize> Deref for Secret<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> DerefMut for Secret<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Содержимое не выводится, чтобы секрет не попал в логи
+impl<T: Zeroize> std::fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
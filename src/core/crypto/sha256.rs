@@ -1,8 +1,16 @@
+use super::secret::Zeroize;
+
 const INITIAL_HASH: [u32; 8] = [
     0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
     0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
 ];
 
+/// SHA-224: вторые 32 бита дробных частей корней 9-го..16-го простых чисел
+const INITIAL_HASH_224: [u32; 8] = [
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939,
+    0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
+
 const K: [u32; 64] = [
     0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
     0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
@@ -21,10 +29,28 @@ pub struct Sha256 {
     length: u64,
 }
 
+/// Состояние и буфер содержат данные ключа или пароля
+impl Drop for Sha256 {
+    fn drop(&mut self) {
+        self.hash.zeroize();
+        self.buffer.zeroize();
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Sha256 {
     pub fn new() -> Self {
+        Self::with_initial_hash(INITIAL_HASH)
+    }
+
+    fn with_initial_hash(hash: [u32; 8]) -> Self {
         Sha256 {
-            hash: INITIAL_HASH,
+            hash,
             buffer: [0; 64],
             length: 0,
         }
@@ -67,13 +93,8 @@ impl Sha256 {
 
     fn process_block(&mut self) {
         let mut words = [0u32; 64];
-        for i in 0..16 {
-            words[i] = u32::from_be_bytes([
-                self.buffer[i * 4],
-                self.buffer[i * 4 + 1],
-                self.buffer[i * 4 + 2],
-                self.buffer[i * 4 + 3],
-            ]);
+        for (word, chunk) in words.iter_mut().zip(self.buffer.chunks_exact(4)) {
+            *word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
         }
 
         for i in 16..64 {
@@ -120,6 +141,16 @@ impl Sha256 {
     }
 
     pub fn finalize(mut self) -> [u8; 32] {
+        self.pad();
+        let mut result = [0u8; 32];
+        for (i, &word) in self.hash.iter().enumerate() {
+            result[i * 4..(i + 1) * 4].copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+
+    /// Дополнение и длина сообщения; после него `hash` — итоговое состояние
+    fn pad(&mut self) {
         let len_bits = self.length * 8;
         let buffer_len = (self.length % 64) as usize;
 
@@ -139,11 +170,32 @@ impl Sha256 {
             self.buffer[56..64].copy_from_slice(&len_bits.to_be_bytes());
             self.process_block();
         }
+    }
+}
 
-        let mut result = [0u8; 32];
-        for (i, &word) in self.hash.iter().enumerate() {
-            result[i * 4..(i + 1) * 4].copy_from_slice(&word.to_be_bytes());
-        }
+/// SHA-224: сжатие SHA-256 с другими начальными значениями, результат — первые 28 байт
+#[derive(Clone)]
+pub struct Sha224(Sha256);
+
+impl Default for Sha224 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha224 {
+    pub fn new() -> Self {
+        Sha224(Sha256::with_initial_hash(INITIAL_HASH_224))
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.0.update(data);
+        self
+    }
+
+    pub fn finalize(self) -> [u8; 28] {
+        let mut result = [0u8; 28];
+        result.copy_from_slice(&self.0.finalize()[..28]);
         result
     }
 }
\ No newline at end of file
@@ -0,0 +1,196 @@
+//! SHA-512 и SHA-384 (FIPS 180-4): 64-битные слова, блок 128 байт, 80 раундов
+use super::secret::Zeroize;
+
+const INITIAL_HASH: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+/// SHA-384: дробные части корней 9-го..16-го простых чисел
+const INITIAL_HASH_384: [u64; 8] = [
+    0xcbbb9d5dc1059ed8, 0x629a292a367cd507, 0x9159015a3070dd17, 0x152fecd8f70e5939,
+    0x67332667ffc00b31, 0x8eb44a8768581511, 0xdb0c2e0d64f98fa7, 0x47b5481dbefa4fa4,
+];
+
+const K: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+#[derive(Clone)]
+pub struct Sha512 {
+    hash: [u64; 8],
+    buffer: [u8; 128],
+    /// Длина сообщения в байтах; в дополнение пишется 128-битная длина в битах
+    length: u128,
+}
+
+/// Состояние и буфер содержат данные ключа или пароля
+impl Drop for Sha512 {
+    fn drop(&mut self) {
+        self.hash.zeroize();
+        self.buffer.zeroize();
+    }
+}
+
+impl Default for Sha512 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha512 {
+    pub fn new() -> Self {
+        Self::with_initial_hash(INITIAL_HASH)
+    }
+
+    fn with_initial_hash(hash: [u64; 8]) -> Self {
+        Sha512 {
+            hash,
+            buffer: [0; 128],
+            length: 0,
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        let mut data = data;
+        let buffer_len = (self.length % 128) as usize;
+
+        // Дополняем частично заполненный буфер
+        if buffer_len > 0 {
+            let to_copy = (128 - buffer_len).min(data.len());
+            self.buffer[buffer_len..buffer_len + to_copy].copy_from_slice(&data[..to_copy]);
+            self.length += to_copy as u128;
+            data = &data[to_copy..];
+            if buffer_len + to_copy == 128 {
+                self.process_block();
+            }
+        }
+
+        // Целые блоки и остаток в буфер
+        let mut blocks = data.chunks_exact(128);
+        for block in &mut blocks {
+            self.buffer.copy_from_slice(block);
+            self.process_block();
+            self.length += 128;
+        }
+        let remaining = blocks.remainder();
+        if !remaining.is_empty() {
+            self.buffer[..remaining.len()].copy_from_slice(remaining);
+            self.length += remaining.len() as u128;
+        }
+        self
+    }
+
+    fn process_block(&mut self) {
+        let mut words = [0u64; 80];
+        for (word, chunk) in words.iter_mut().zip(self.buffer.chunks_exact(8)) {
+            *word = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+
+        for i in 16..80 {
+            let s0 = words[i-15].rotate_right(1) ^ words[i-15].rotate_right(8) ^ (words[i-15] >> 7);
+            let s1 = words[i-2].rotate_right(19) ^ words[i-2].rotate_right(61) ^ (words[i-2] >> 6);
+            words[i] = words[i-16].wrapping_add(s0).wrapping_add(words[i-7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.hash;
+
+        for i in 0..80 {
+            let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+            let ch = (e & f) ^ ((!e) & g);
+            let temp1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(words[i]);
+            let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        for (state, value) in self.hash.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(value);
+        }
+        words.zeroize();
+    }
+
+    pub fn finalize(mut self) -> [u8; 64] {
+        self.pad();
+        let mut result = [0u8; 64];
+        for (i, &word) in self.hash.iter().enumerate() {
+            result[i * 8..(i + 1) * 8].copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+
+    /// Дополнение и длина сообщения; после него `hash` — итоговое состояние
+    fn pad(&mut self) {
+        let len_bits = self.length * 8;
+        let buffer_len = (self.length % 128) as usize;
+
+        self.buffer[buffer_len] = 0x80;
+        let after_80 = buffer_len + 1;
+
+        // На длину нужно 16 байт; если их не осталось — ещё один блок
+        if after_80 > 112 {
+            self.buffer[after_80..].fill(0);
+            self.process_block();
+            self.buffer[..112].fill(0);
+        } else {
+            self.buffer[after_80..112].fill(0);
+        }
+        self.buffer[112..].copy_from_slice(&len_bits.to_be_bytes());
+        self.process_block();
+    }
+}
+
+/// SHA-384: сжатие SHA-512 с другими начальными значениями, результат — первые 48 байт
+#[derive(Clone)]
+pub struct Sha384(Sha512);
+
+impl Default for Sha384 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha384 {
+    pub fn new() -> Self {
+        Sha384(Sha512::with_initial_hash(INITIAL_HASH_384))
+    }
+
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.0.update(data);
+        self
+    }
+
+    pub fn finalize(self) -> [u8; 48] {
+        let mut result = [0u8; 48];
+        result.copy_from_slice(&self.0.finalize()[..48]);
+        result
+    }
+}
@@ -0,0 +1,59 @@
+//! Детерминированный AEAD в духе SIV (RFC 5297): синтетический IV — это PRF от
+//! AAD, необязательного nonce и открытого текста, затем CTR с этим IV.
+//!
+//! PRF — HMAC-SHA256, усечённый до блока, вместо CMAC/S2V. Повтор nonce (или его
+//! отсутствие) раскрывает только равенство сообщений, а не гамму.
+use super::cipher::{Cipher, BLOCK_SIZE};
+use super::hmac::{ct_eq, HmacSha256};
+use super::secret::{Secret, Zeroize};
+
+pub const SIV_LEN: usize = BLOCK_SIZE;
+
+/// Независимые подключи для PRF и для CTR из одного 32-байтного ключа
+fn subkey(key: &[u8; 32], label: &[u8]) -> [u8; 32] {
+    let mut mac = HmacSha256::new(key);
+    mac.update(label);
+    mac.finalize()
+}
+
+/// Каждая часть кодируется с длиной, чтобы границы между ними нельзя было сдвинуть
+fn synthetic_iv(mac_key: &[u8; 32], nonce: Option<&[u8]>, aad: &[u8], plaintext: &[u8]) -> [u8; SIV_LEN] {
+    let mut mac = HmacSha256::new(mac_key);
+    mac.update(&(aad.len() as u64).to_be_bytes()).update(aad);
+    match nonce {
+        Some(nonce) => mac.update(&[1]).update(&(nonce.len() as u64).to_be_bytes()).update(nonce),
+        None => mac.update(&[0]),
+    };
+    mac.update(&(plaintext.len() as u64).to_be_bytes()).update(plaintext);
+    let tag = mac.finalize();
+    *arrayref::array_ref!(tag, 0, SIV_LEN)
+}
+
+/// Возвращает `siv || шифртекст`. Одинаковые входы дают одинаковый результат
+pub fn encrypt_siv(key: &[u8; 32], nonce: Option<&[u8]>, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let siv = synthetic_iv(&Secret::new(subkey(key, b"crypto-app siv mac key")), nonce, aad, plaintext);
+    let cipher = Cipher::new(subkey(key, b"crypto-app siv enc key"));
+
+    let mut out = siv.to_vec();
+    out.extend(cipher.apply_ctr(plaintext, &siv)?);
+    Ok(out)
+}
+
+/// Ожидает `siv || шифртекст`, как возвращает `encrypt_siv`. Открытый текст
+/// отдаётся, только если пересчитанный IV совпал
+pub fn decrypt_siv(key: &[u8; 32], nonce: Option<&[u8]>, aad: &[u8], data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if data.len() < SIV_LEN {
+        return Err("Invalid ciphertext length");
+    }
+    let siv = *arrayref::array_ref!(data, 0, SIV_LEN);
+    let cipher = Cipher::new(subkey(key, b"crypto-app siv enc key"));
+    let mut plaintext = cipher.apply_ctr(&data[SIV_LEN..], &siv)?;
+
+    let expected = synthetic_iv(&Secret::new(subkey(key, b"crypto-app siv mac key")), nonce, aad, &plaintext);
+    if !ct_eq(&expected, &siv) {
+        // Непроверенный открытый текст не должен остаться в памяти
+        plaintext.zeroize();
+        return Err("Authentication failed");
+    }
+    Ok(plaintext)
+}
@@ -1,5 +1,4 @@
 use getrandom::getrandom;
-use std::f64::consts::FRAC_1_PI;
 
 pub struct RCTMPrng {
     x: f64,
@@ -29,7 +28,7 @@ impl RCTMPrng {
     }
 
     pub fn new(mu: f64, x0: f64) -> Result<Self, &'static str> {
-        if mu < 2.0 || mu >= 100.0 || (mu.floor() - mu).abs() < f64::EPSILON {
+        if !(2.0..100.0).contains(&mu) || (mu.floor() - mu).abs() < f64::EPSILON {
             return Err("mu должен быть в диапазоне [2, 100) и не быть целым числом");
         }
         if x0 <= 0.0 || x0 >= 1.0 {
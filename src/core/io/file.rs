@@ -1,26 +1,199 @@
-use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
-use super::meta::Metadata;
-use crate::core::crypto::{keygen::derive_key, cipher::Cipher};
+use tempfile::NamedTempFile;
+use super::meta::{Metadata, CipherParams, KdfId, KdfParams, ModeId, PBoxId, SBoxId, MAGIC, LEGACY_LEN, HEADER_TAG_LEN};
+use super::tables::CustomTables;
+use super::stream::{encrypt_stream, decrypt_stream, mac_stream};
+use crate::core::crypto::{keygen::{derive_key, derive_key_salted, derive_key_scrypt}, cipher::{Cipher, CounterLayout, CtrStream}};
+use crate::core::crypto::hmac::{HmacSha256, TAG_LEN, ct_eq};
+use crate::core::crypto::modes::{CipherMode, Cbc, Cfb, Cfb8, Ofb};
+use crate::core::crypto::secret::Secret;
+use crate::core::crypto::hkdf::Hkdf;
+use crate::core::crypto::pbkdf2::pbkdf2;
+use crate::core::crypto::sha256::Sha256;
 
+const IV_LEN: usize = 16;
+
+/// Подключи файла v1, выведенные из мастер-ключа (результата KDF пароля)
+struct FileKeys {
+    encryption: Secret<[u8; 32]>,
+    authentication: Secret<[u8; 32]>,
+    header: Secret<[u8; 32]>,
+}
+
+impl FileKeys {
+    /// Три ключа HKDF-SHA256: соль — соль файла, `info` у каждого свой
+    fn derive(metadata: &Metadata, master: &[u8; 32]) -> Self {
+        let hkdf = Hkdf::<Sha256>::extract(&metadata.salt, master);
+        let expand = |info: &[u8]| hkdf.expand_array::<32>(info).expect("32 bytes is within the HKDF limit");
+        FileKeys {
+            encryption: expand(b"crypto-app v1 encryption key"),
+            authentication: expand(b"crypto-app v1 authentication key"),
+            header: expand(b"crypto-app v1 header key"),
+        }
+    }
+
+    fn header_tag(&self, metadata: &Metadata) -> [u8; HEADER_TAG_LEN] {
+        let mut mac = HmacSha256::new(&self.header[..]);
+        mac.update(&metadata.header_tag_input());
+        mac.finalize()
+    }
+
+    /// Encrypt-then-MAC: тег покрывает заголовок и весь шифртекст
+    fn new_mac(&self, header: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new(&self.authentication[..]);
+        mac.update(header);
+        mac
+    }
+}
+
+/// Мастер-ключ из пароля по KDF и параметрам из заголовка
+fn derive_for(metadata: &Metadata, password: &str) -> Result<Secret<[u8; 32]>, String> {
+    if metadata.kdf == KdfId::BilliardUnsalted {
+        return Ok(Secret::new(derive_key(password.as_bytes())));
+    }
+    let params = KdfParams::from_bytes(metadata.kdf, &metadata.kdf_params)
+        .map_err(|e| format!("Metadata error: {}", e))?;
+    Ok(match params {
+        KdfParams::Billiard => Secret::new(derive_key_salted(password.as_bytes(), &metadata.salt)),
+        KdfParams::Pbkdf2 { iterations } => {
+            let mut key = Secret::new([0u8; 32]);
+            pbkdf2::<Sha256>(password.as_bytes(), &metadata.salt, iterations, &mut key[..])?;
+            key
+        }
+        KdfParams::Scrypt { params, billiard_prehash } => Secret::new(
+            derive_key_scrypt(password.as_bytes(), &metadata.salt, &params, billiard_prehash)?),
+    })
+}
+
+/// Шифр с параметрами SPN из заголовка
+fn cipher_for(metadata: &Metadata, key: &[u8; 32], tables: &CustomTables) -> Result<Cipher, String> {
+    let params = metadata.cipher_params.to_spn(key, tables)
+        .map_err(|e| format!("Metadata error: {}", e))?;
+    Cipher::with_params(*key, &params)
+        .map_err(|e| format!("Cipher error: {}", e))
+}
+
+/// Таблицы должны совпадать с теми, которыми файл зашифрован: с чужими
+/// таблицами расшифрование дало бы мусор, а не ошибку
+fn check_tables(metadata: &Metadata, tables: &CustomTables) -> Result<(), String> {
+    let supplied = (!tables.is_empty()).then(|| tables.fingerprint());
+    match (metadata.table_fingerprint, supplied) {
+        (expected, supplied) if expected == supplied => Ok(()),
+        (None, _) => Err("File was not encrypted with custom S-box/P-box tables".into()),
+        (Some(_), None) => Err("File was encrypted with custom S-box/P-box tables; supply them to decrypt".into()),
+        (Some(_), Some(_)) => Err("S-box/P-box tables do not match the ones the file was encrypted with".into()),
+    }
+}
+
+/// Режим из заголовка, инициализированный IV файла
+fn mode_for<'a>(metadata: &Metadata, cipher: &'a Cipher) -> Box<dyn CipherMode + 'a> {
+    match metadata.mode {
+        ModeId::CtrHmac => Box::new(CtrStream::with_layout(cipher, metadata.iv, metadata.counter_layout())),
+        ModeId::CbcHmac => Box::new(Cbc::new(cipher, metadata.iv)),
+        ModeId::CfbHmac => Box::new(Cfb::new(cipher, metadata.iv)),
+        ModeId::Cfb8Hmac => Box::new(Cfb8::new(cipher, metadata.iv)),
+        ModeId::OfbHmac => Box::new(Ofb::new(cipher, metadata.iv)),
+    }
+}
+
+/// Читает заголовок с начала файла; возвращает разобранные метаданные и исходные байты
+fn read_header(input: &mut File) -> Result<(Metadata, Vec<u8>), String> {
+    let mut header = Vec::with_capacity(LEGACY_LEN);
+    // 48 байт хватает и на весь заголовок v0, и на поле длины заголовка v1
+    Read::take(&mut *input, LEGACY_LEN as u64).read_to_end(&mut header)
+        .map_err(|e| format!("Error reading file: {}", e))?;
+
+    if header.starts_with(&MAGIC) && header.len() > 10 {
+        let header_len = u16::from_be_bytes([header[9], header[10]]) as usize;
+        if header_len > header.len() {
+            Read::take(&mut *input, (header_len - header.len()) as u64).read_to_end(&mut header)
+                .map_err(|e| format!("Error reading file: {}", e))?;
+        }
+    }
+
+    let metadata = Metadata::from_bytes(&header)
+        .map_err(|e| format!("Metadata error: {}", e))?;
+    header.truncate(metadata.header_len());
+    Ok((metadata, header))
+}
+
+/// Параметры шифрования, которые записываются в заголовок файла.
+/// Заданные в `tables` таблицы заменяют S-box/P-box из `params`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptOptions {
+    pub mode: ModeId,
+    pub params: CipherParams,
+    pub tables: CustomTables,
+    pub kdf: KdfParams,
+}
+
+impl Default for EncryptOptions {
+    fn default() -> Self {
+        EncryptOptions {
+            mode: ModeId::CtrHmac,
+            params: CipherParams::CLASSIC,
+            tables: CustomTables::default(),
+            kdf: KdfParams::default(),
+        }
+    }
+}
+
+/// Параметры расшифрования, которых нет в заголовке
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecryptOptions {
+    /// Таблицы, которыми был зашифрован файл (проверяются по отпечатку)
+    pub tables: CustomTables,
+    /// Расшифровывать файлы v0: у них нет тега, и целостность не проверяется
+    pub allow_legacy: bool,
+}
 
 pub fn encrypt_file(input_path: &Path, output_path: &Path, password: &str) -> Result<(), String> {
-    let data = fs::read(input_path)
+    encrypt_file_with(input_path, output_path, password, &EncryptOptions::default())
+}
+
+pub fn encrypt_file_with(
+    input_path: &Path,
+    output_path: &Path,
+    password: &str,
+    options: &EncryptOptions,
+) -> Result<(), String> {
+    let mut input = File::open(input_path)
         .map_err(|e| e.to_string())?; // Преобразование ошибки
-    
-    let mut metadata = Metadata::new(); 
-    
-    let key = derive_key(password.as_bytes());
-    let cipher = Cipher::new(key);
-    
-    let encrypted_data = cipher.encrypt(&data, &metadata.iv);
-    
-    let mut output = metadata.to_bytes();
-    output.extend(encrypted_data);
-    
-    fs::write(output_path, output)
+
+    let mut params = options.params;
+    if options.tables.s_box.is_some() {
+        params.s_box = SBoxId::Custom;
+    }
+    if options.tables.p_box.is_some() {
+        params.p_box = PBoxId::Custom;
+    }
+    let mut metadata = Metadata::for_mode(options.mode);
+    metadata.set_cipher_params(params);
+    metadata.set_table_fingerprint((!options.tables.is_empty()).then(|| options.tables.fingerprint()));
+    metadata.set_kdf(options.kdf);
+
+    // Тег заголовка вычисляется, когда все остальные поля уже заполнены
+    let master = derive_for(&metadata, password)?;
+    let keys = FileKeys::derive(&metadata, &master);
+    metadata.set_header_tag(keys.header_tag(&metadata));
+    let cipher = cipher_for(&metadata, &keys.encryption, &options.tables)?;
+
+    let header = metadata.to_bytes();
+    let mut mac = keys.new_mac(&header);
+
+    let mut output = create_output(output_path)?;
+    output.write_all(&header)
+        .map_err(|e| e.to_string())?;
+
+    let mut mode = mode_for(&metadata, &cipher);
+    encrypt_stream(&mut input, &mut output, mode.as_mut(), &mut mac)
+        .map_err(|e| e.to_string())?;
+
+    output.write_all(&mac.finalize())
         .map_err(|e| e.to_string())?; // Преобразование ошибки
-    Ok(())
+    persist_output(output, output_path)
 }
 
 pub fn decrypt_file(
@@ -28,24 +201,274 @@ pub fn decrypt_file(
     output_path: &Path,
     password: &str,
 ) -> Result<(), String> {
-    let encrypted_data = fs::read(input_path)
+    decrypt_file_with(input_path, output_path, password, &DecryptOptions::default())
+}
+
+/// Выход пишется во временный файл рядом с `output_path` и переносится на место
+/// только после успеха: вход и выход могут совпадать, а при ошибке выход не портится
+fn create_output(output_path: &Path) -> Result<BufWriter<NamedTempFile>, String> {
+    let dir = match output_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    NamedTempFile::new_in(dir)
+        .map(BufWriter::new)
+        .map_err(|e| format!("Write error: {}", e))
+}
+
+fn persist_output(output: BufWriter<NamedTempFile>, output_path: &Path) -> Result<(), String> {
+    output.into_inner()
+        .map_err(|e| format!("Write error: {}", e.error()))?
+        .persist(output_path)
+        .map_err(|e| format!("Write error: {}", e.error))?;
+    Ok(())
+}
+
+/// Открытый файл: ключ шифрования и расположение шифртекста, `input` стоит на его начале
+struct Opened {
+    input: File,
+    metadata: Metadata,
+    encryption: Secret<[u8; 32]>,
+    body_offset: u64,
+    body_len: u64,
+    /// MAC, уже получивший заголовок, и ожидаемый тег; у v0 тега нет
+    pending: Option<(HmacSha256, [u8; TAG_LEN])>,
+}
+
+fn check_tag(mac: HmacSha256, tag: &[u8; TAG_LEN]) -> Result<(), String> {
+    if !ct_eq(&mac.finalize(), tag) {
+        return Err("Authentication failed: wrong password or corrupted file".into());
+    }
+    Ok(())
+}
+
+/// Открывает файл и проверяет тег отдельным проходом. Сам шифртекст не расшифровывается
+fn open_verified(input_path: &Path, password: &str, options: &DecryptOptions) -> Result<Opened, String> {
+    let mut opened = open_file(input_path, password, options)?;
+    if let Some((mut mac, tag)) = opened.pending.take() {
+        mac_stream(&mut opened.input, opened.body_len, &mut mac)
+            .map_err(|e| format!("Error reading file: {}", e))?;
+        check_tag(mac, &tag)?;
+    }
+    Ok(opened)
+}
+
+/// Читает заголовок, проверяет его тег и выбирает ключ. Тег шифртекста остаётся
+/// в `pending`: его проверяет вызывающий
+fn open_file(input_path: &Path, password: &str, options: &DecryptOptions) -> Result<Opened, String> {
+    let mut input = File::open(input_path)
         .map_err(|e| format!("Error reading file: {}", e))?;
+    let file_len = input.metadata()
+        .map_err(|e| format!("Error reading file: {}", e))?
+        .len();
 
-    if encrypted_data.len() < 48 {
+    let (metadata, header) = read_header(&mut input)?;
+    check_tables(&metadata, &options.tables)?;
+    if metadata.is_legacy() {
+        return open_legacy(input, metadata, file_len, password, options);
+    }
+
+    let header_len = header.len() as u64;
+    if file_len < header_len + TAG_LEN as u64 {
         return Err("File too short to contain metadata".into());
     }
+    let body_len = file_len - header_len - TAG_LEN as u64;
 
-    let metadata = Metadata::from_bytes(&encrypted_data[0..48])
-        .map_err(|e| format!("Metadata error: {}", e))?;
+    let mut tag = [0u8; TAG_LEN];
+    input.seek(SeekFrom::End(-(TAG_LEN as i64)))
+        .and_then(|_| input.read_exact(&mut tag))
+        .map_err(|e| format!("Error reading file: {}", e))?;
+
+    let master = derive_for(&metadata, password)?;
+    let keys = FileKeys::derive(&metadata, &master);
+
+    // Тег заголовка проверяется сразу: неверный пароль обнаруживается без чтения файла
+    if !metadata.header_tag.is_some_and(|expected| ct_eq(&keys.header_tag(&metadata), &expected)) {
+        return Err("Authentication failed: wrong password or corrupted header".into());
+    }
+
+    let mac = keys.new_mac(&header);
+    input.seek(SeekFrom::Start(header_len))
+        .map_err(|e| format!("Error reading file: {}", e))?;
+
+    Ok(Opened { input, metadata, encryption: keys.encryption, body_offset: header_len, body_len, pending: Some((mac, tag)) })
+}
+
+/// Файл v0 — формат до появления заголовка и тега: `salt || iv || iv || шифртекст`,
+/// ключ — бильярдный KDF без соли. Проверить пароль и целостность такого файла
+/// нельзя, поэтому он открывается только с `allow_legacy`
+fn open_legacy(
+    mut input: File,
+    metadata: Metadata,
+    file_len: u64,
+    password: &str,
+    options: &DecryptOptions,
+) -> Result<Opened, String> {
+    let body_offset = (LEGACY_LEN + IV_LEN) as u64;
+    if file_len < body_offset {
+        return Err("File too short to contain metadata".into());
+    }
+    // Исходная версия всегда повторяла IV после заголовка. Без повтора это не v0,
+    // а файл без магии, например v1 с повреждённым заголовком
+    let mut repeated_iv = [0u8; IV_LEN];
+    input.seek(SeekFrom::Start(LEGACY_LEN as u64))
+        .and_then(|_| input.read_exact(&mut repeated_iv))
+        .map_err(|e| format!("Error reading file: {}", e))?;
+    if repeated_iv != metadata.iv {
+        return Err("Authentication failed: unrecognized or corrupted header".into());
+    }
+    if !options.allow_legacy {
+        return Err("Unauthenticated legacy file (format v0): its integrity cannot be verified; \
+                    use --legacy to decrypt it anyway".into());
+    }
+    // У v0 нет подключей: шифрует сам мастер-ключ
+    let encryption = derive_for(&metadata, password)?;
+    Ok(Opened { input, metadata, encryption, body_offset, body_len: file_len - body_offset, pending: None })
+}
+
+pub fn decrypt_file_with(
+    input_path: &Path,
+    output_path: &Path,
+    password: &str,
+    options: &DecryptOptions,
+) -> Result<(), String> {
+    let Opened { mut input, metadata, encryption, body_len, mut pending, .. } =
+        open_file(input_path, password, options)?;
+    let cipher = cipher_for(&metadata, &encryption, &options.tables)?;
+
+    // Один проход: тег считается по тем же байтам, что расшифровываются, а
+    // результат появляется на месте `output_path` только после проверки тега
+    let mut output = create_output(output_path)?;
+    let mut mode = mode_for(&metadata, &cipher);
+    let decrypted = decrypt_stream(
+        &mut input, &mut output, body_len, mode.as_mut(), pending.as_mut().map(|(mac, _)| mac));
+    // Ошибка дополнения при неверном пароле — это ошибка аутентификации,
+    // а ошибка чтения оставляет MAC недосчитанным и сообщается как есть
+    if let Err(e) = &decrypted
+        && e.kind() != std::io::ErrorKind::InvalidData
+    {
+        return Err(format!("Decryption error: {}", e));
+    }
+    if let Some((mac, tag)) = pending {
+        check_tag(mac, &tag)?;
+    }
+    decrypted.map_err(|e| format!("Decryption error: {}", e))?;
 
-    let key = derive_key(password.as_bytes());
-    let cipher = Cipher::new(key);
+    persist_output(output, output_path)
+}
+
+/// Расшифрование файла CTR с произвольным доступом: `Read + Seek` по открытому
+/// тексту. Позиция 0 — первый байт исходного файла, заголовок и тег пропускаются.
+/// Тег проверяется один раз при открытии (файл читается целиком, но не
+/// расшифровывается); дальше расшифровываются только запрошенные байты
+pub struct DecryptReader {
+    input: File,
+    cipher: Cipher,
+    iv: [u8; 16],
+    layout: CounterLayout,
+    body_offset: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl DecryptReader {
+    pub fn open(input_path: &Path, password: &str) -> Result<Self, String> {
+        Self::open_with(input_path, password, &DecryptOptions::default())
+    }
+
+    pub fn open_with(input_path: &Path, password: &str, options: &DecryptOptions) -> Result<Self, String> {
+        let opened = open_verified(input_path, password, options)?;
+        if opened.metadata.mode != ModeId::CtrHmac {
+            return Err("Random access is only supported for CTR files".into());
+        }
+        let cipher = cipher_for(&opened.metadata, &opened.encryption, &options.tables)?;
+        Ok(DecryptReader {
+            input: opened.input,
+            cipher,
+            iv: opened.metadata.iv,
+            layout: opened.metadata.counter_layout(),
+            body_offset: opened.body_offset,
+            len: opened.body_len,
+            pos: 0,
+        })
+    }
+
+    /// Длина открытого текста
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
 
-    let decrypted_data = cipher.decrypt(&encrypted_data[48..], &metadata.iv)
+impl Read for DecryptReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let want = (buf.len() as u64).min(self.len.saturating_sub(self.pos)) as usize;
+        if want == 0 {
+            return Ok(0);
+        }
+        self.input.seek(SeekFrom::Start(self.body_offset + self.pos))?;
+        let n = self.input.read(&mut buf[..want])?;
+        self.cipher.apply_keystream_with(self.layout, &self.iv, self.pos, &mut buf[..n])
+            .map_err(std::io::Error::other)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for DecryptReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+        // Как у File: позиция за концом допустима, до начала — нет
+        self.pos = target.ok_or_else(|| std::io::Error::new(
+            std::io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position"))?;
+        Ok(self.pos)
+    }
+}
+
+/// Расшифровывает `len` байт открытого текста начиная со `start` в `output_path`.
+/// Диапазон, выходящий за конец файла, обрезается
+pub fn decrypt_range(
+    input_path: &Path,
+    output_path: &Path,
+    password: &str,
+    start: u64,
+    len: u64,
+    options: &DecryptOptions,
+) -> Result<(), String> {
+    let mut reader = DecryptReader::open_with(input_path, password, options)?;
+    if start > reader.len() {
+        return Err(format!("Range start {} is beyond the end of the data ({} bytes)", start, reader.len()));
+    }
+    let mut output = create_output(output_path)?;
+    reader.seek(SeekFrom::Start(start))
+        .and_then(|_| std::io::copy(&mut Read::take(&mut reader, len), &mut output))
         .map_err(|e| format!("Decryption error: {}", e))?;
+    persist_output(output, output_path)
+}
 
-    fs::write(output_path, decrypted_data)
-        .map_err(|e| format!("Write error: {}", e))?;
+/// HMAC-SHA256 содержимого файла с общим секретом `key` (без шифрования)
+pub fn mac_file(input_path: &Path, key: &[u8]) -> Result<[u8; TAG_LEN], String> {
+    let mut input = File::open(input_path)
+        .map_err(|e| format!("Error reading file: {}", e))?;
+    let mut mac = HmacSha256::new(key);
+    std::io::copy(&mut input, &mut mac)
+        .map_err(|e| format!("Error reading file: {}", e))?;
+    Ok(mac.finalize())
+}
 
-    Ok(())
-}
\ No newline at end of file
+/// Проверяет тег, полученный `mac_file`; допускается усечённый тег (не короче 16 байт)
+pub fn verify_file_mac(input_path: &Path, key: &[u8], tag: &[u8]) -> Result<bool, String> {
+    let mut input = File::open(input_path)
+        .map_err(|e| format!("Error reading file: {}", e))?;
+    let mut mac = HmacSha256::new(key);
+    std::io::copy(&mut input, &mut mac)
+        .map_err(|e| format!("Error reading file: {}", e))?;
+    Ok(mac.verify(tag))
+}
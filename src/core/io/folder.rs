@@ -1,15 +1,25 @@
 //! Directory encryption/decryption operations
-use std::fs::{self, File};
-use std::path::{Path, PathBuf};
+use std::fs::File;
+use std::path::Path;
 use tar::{Builder, Archive};
 use tempfile::NamedTempFile;
-use super::file::{encrypt_file, decrypt_file};
+use super::file::{encrypt_file_with, decrypt_file_with, EncryptOptions, DecryptOptions};
 
 /// Encrypt a directory into a tar archive and encrypt it
 pub fn encrypt_directory(
     input_dir: &Path,
     output_path: &Path,
     password: &str,
+) -> Result<(), String> {
+    encrypt_directory_with(input_dir, output_path, password, &EncryptOptions::default())
+}
+
+/// Same as `encrypt_directory`, with explicit encryption options
+pub fn encrypt_directory_with(
+    input_dir: &Path,
+    output_path: &Path,
+    password: &str,
+    options: &EncryptOptions,
 ) -> Result<(), String> {
     // Validate input directory exists
     if !input_dir.exists() {
@@ -35,7 +45,7 @@ pub fn encrypt_directory(
     } // File is automatically closed here
 
     // Encrypt the tar file
-    encrypt_file(temp_file.path(), output_path, password)?;
+    encrypt_file_with(temp_file.path(), output_path, password, options)?;
     
     // Explicitly persist and delete temp file (optional)
     temp_file.close()
@@ -49,6 +59,16 @@ pub fn decrypt_directory(
     encrypted_path: &Path,
     output_dir: &Path,
     password: &str,
+) -> Result<(), String> {
+    decrypt_directory_with(encrypted_path, output_dir, password, &DecryptOptions::default())
+}
+
+/// Same as `decrypt_directory`, with explicit decryption options
+pub fn decrypt_directory_with(
+    encrypted_path: &Path,
+    output_dir: &Path,
+    password: &str,
+    options: &DecryptOptions,
 ) -> Result<(), String> {
     // Validate encrypted file exists
     if !encrypted_path.exists() {
@@ -63,7 +83,7 @@ pub fn decrypt_directory(
         .map_err(|e| format!("Temp file error: {}", e))?;
 
     // Decrypt to temporary file
-    decrypt_file(encrypted_path, temp_file.path(), password)?;
+    decrypt_file_with(encrypted_path, temp_file.path(), password, options)?;
 
     // Unpack tar archive
     {
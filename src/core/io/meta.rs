@@ -1,51 +1,624 @@
 //! Metadata handling for encrypted files
+//!
+//! Формат v1 (все целые — big-endian):
+//!
+//! | offset  | size | field                         |
+//! |---------|------|-------------------------------|
+//! | 0       | 8    | magic `SPNCRYPT`              |
+//! | 8       | 1    | version                       |
+//! | 9       | 2    | header length (вся шапка)     |
+//! | 11      | 1    | cipher id                     |
+//! | 12      | 1    | mode id                       |
+//! | 13      | 1    | kdf id                        |
+//! | 14      | 1    | flags                         |
+//! | 15      | 1    | kdf params length `n`         |
+//! | 16      | n    | kdf params                    |
+//! |         |      | необязательные секции         |
+//! | ...     | 32   | salt                          |
+//! | ...     | 16   | iv (nonce + counter)          |
+//!
+//! `FLAG_COUNTER64` обязателен: IV — это `nonce (8) || counter (8)`
+//! (`CounterLayout::Wide64`), и файл может быть длиннее 64 ГиБ.
+//!
+//! Необязательные секции идут в порядке битов `flags`, каждая — `len (1) || данные`:
+//!
+//! * `FLAG_CIPHER_PARAMS` — `rounds || s-box id || p-box id || key schedule`.
+//!   Без секции шифр классический (`CipherParams::CLASSIC`).
+//! * `FLAG_TABLE_FINGERPRINT` — 32 байта отпечатка пользовательских таблиц
+//!   (`CustomTables::fingerprint`).
+//! * `FLAG_HEADER_TAG` — 32 байта HMAC заголовка, обязателен. Ключи шифрования,
+//!   MAC и заголовка выведены из мастер-ключа через HKDF; тег считается по
+//!   заголовку, в котором само поле тега заполнено нулями.
+//!
+//! Параметры KDF зависят от его id: у бильярдного KDF их нет, у PBKDF2 —
+//! число итераций (`u32`), у scrypt — `log2 N (1) || r (4) || p (4) || pre-hash (1)`,
+//! где pre-hash 1 означает, что на вход scrypt подан результат бильярдного KDF.
+//!
+//! Формат v0 не имеет шапки: `salt || iv`, 48 байт. За ним идут повтор IV и
+//! шифртекст CTR с 32-битным счётчиком (`CounterLayout::Legacy32`); тега у
+//! файлов v0 нет.
+use std::fmt;
+use crate::core::crypto::cipher::{CounterLayout, KeySchedule, SpnParams, MAX_ROUNDS};
+use crate::core::crypto::pbkdf2::MAX_ITERATIONS;
+use crate::core::crypto::scrypt::ScryptParams;
+use crate::core::crypto::{s_box::{S_BOX, generate_s_box}, p_box::{P_BOX, TRANSPOSE_P_BOX}};
 use crate::core::io::RCTMPrng::RCTMPrng;
+use crate::core::io::tables::CustomTables;
+
+pub const MAGIC: [u8; 8] = *b"SPNCRYPT";
+pub const CURRENT_VERSION: u8 = 1;
+/// Длина заголовка v0 (salt || iv)
+pub const LEGACY_LEN: usize = 48;
+
+const FIXED_LEN: usize = 16;
+const SALT_LEN: usize = 32;
+const IV_LEN: usize = 16;
+/// Счётчик CTR 64-битный (`CounterLayout::Wide64`)
+pub const FLAG_COUNTER64: u8 = 0x01;
+/// В заголовке есть секция параметров SPN
+pub const FLAG_CIPHER_PARAMS: u8 = 0x02;
+/// В заголовке есть отпечаток пользовательских S-box/P-box
+pub const FLAG_TABLE_FINGERPRINT: u8 = 0x04;
+/// Подключи выведены через HKDF, в заголовке есть его HMAC
+pub const FLAG_HEADER_TAG: u8 = 0x08;
+const KNOWN_FLAGS: u8 = FLAG_COUNTER64 | FLAG_CIPHER_PARAMS | FLAG_TABLE_FINGERPRINT | FLAG_HEADER_TAG;
+/// Флаги, без которых файл v1 не читается
+const REQUIRED_FLAGS: u8 = FLAG_COUNTER64 | FLAG_HEADER_TAG;
+const FINGERPRINT_LEN: usize = 32;
+pub const HEADER_TAG_LEN: usize = 32;
+const CIPHER_PARAMS_LEN: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherId {
+    /// 128-битный SPN из `core::crypto::cipher`
+    Spn128 = 1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeId {
+    /// CTR + HMAC-SHA256 (encrypt-then-MAC)
+    CtrHmac = 1,
+    /// CBC с дополнением PKCS#7 + HMAC-SHA256
+    CbcHmac = 2,
+    /// CFB с полным блоком обратной связи + HMAC-SHA256
+    CfbHmac = 3,
+    /// CFB-8 + HMAC-SHA256
+    Cfb8Hmac = 4,
+    /// OFB + HMAC-SHA256
+    OfbHmac = 5,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KdfId {
+    /// Бильярдный KDF без соли (только v0)
+    BilliardUnsalted = 0,
+    /// Бильярдный KDF с солью из заголовка
+    Billiard = 1,
+    /// PBKDF2-HMAC-SHA256 с солью из заголовка
+    Pbkdf2 = 2,
+    /// scrypt, возможно поверх бильярдного KDF
+    Scrypt = 3,
+}
+
+/// KDF вместе с параметрами, которые хранятся в заголовке
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KdfParams {
+    #[default]
+    Billiard,
+    Pbkdf2 { iterations: u32 },
+    Scrypt { params: ScryptParams, billiard_prehash: bool },
+}
+
+impl KdfParams {
+    pub fn id(&self) -> KdfId {
+        match self {
+            Self::Billiard => KdfId::Billiard,
+            Self::Pbkdf2 { .. } => KdfId::Pbkdf2,
+            Self::Scrypt { .. } => KdfId::Scrypt,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Billiard => Vec::new(),
+            Self::Pbkdf2 { iterations } => iterations.to_be_bytes().to_vec(),
+            Self::Scrypt { params, billiard_prehash } => {
+                let mut bytes = vec![params.log_n];
+                bytes.extend_from_slice(&params.r.to_be_bytes());
+                bytes.extend_from_slice(&params.p.to_be_bytes());
+                bytes.push(*billiard_prehash as u8);
+                bytes
+            }
+        }
+    }
+
+    /// Разбирает параметры KDF из заголовка v1
+    pub fn from_bytes(kdf: KdfId, data: &[u8]) -> Result<Self, MetaError> {
+        match kdf {
+            KdfId::Billiard if data.is_empty() => Ok(Self::Billiard),
+            KdfId::Pbkdf2 => {
+                let iterations = u32::from_be_bytes(data.try_into().map_err(|_| MetaError::BadKdfParams)?);
+                if iterations == 0 || iterations > MAX_ITERATIONS {
+                    return Err(MetaError::BadKdfParams);
+                }
+                Ok(Self::Pbkdf2 { iterations })
+            }
+            KdfId::Scrypt => {
+                let &[log_n, r0, r1, r2, r3, p0, p1, p2, p3, prehash] = data else {
+                    return Err(MetaError::BadKdfParams);
+                };
+                let r = u32::from_be_bytes([r0, r1, r2, r3]);
+                let p = u32::from_be_bytes([p0, p1, p2, p3]);
+                let params = ScryptParams::new(log_n, r, p).map_err(|_| MetaError::BadKdfParams)?;
+                let billiard_prehash = match prehash {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(MetaError::BadKdfParams),
+                };
+                Ok(Self::Scrypt { params, billiard_prehash })
+            }
+            _ => Err(MetaError::BadKdfParams),
+        }
+    }
+}
+
+
+/// Встроенные S-box
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SBoxId {
+    Aes = 0,
+    /// Генерируется из ключа файла (`generate_s_box`)
+    Keyed = 1,
+    /// Загружен пользователем (`CustomTables`)
+    Custom = 2,
+}
+
+/// Встроенные P-box
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PBoxId {
+    /// Разворот порядка битов (исходный `P_BOX`)
+    Reverse = 0,
+    /// `TRANSPOSE_P_BOX`
+    Transpose = 1,
+    /// Загружен пользователем (`CustomTables`)
+    Custom = 2,
+}
+
+/// Параметры SPN в том виде, в каком они хранятся в заголовке
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CipherParams {
+    pub rounds: u8,
+    pub s_box: SBoxId,
+    pub p_box: PBoxId,
+    pub key_schedule: KeySchedule,
+}
+
+impl Default for CipherParams {
+    fn default() -> Self {
+        Self::CLASSIC
+    }
+}
+
+impl CipherParams {
+    /// Исходный двухраундовый шифр; файлы без секции параметров
+    pub const CLASSIC: Self = CipherParams {
+        rounds: 2,
+        s_box: SBoxId::Aes,
+        p_box: PBoxId::Reverse,
+        key_schedule: KeySchedule::Split,
+    };
+
+    pub fn hardened(rounds: u8) -> Self {
+        CipherParams { rounds, s_box: SBoxId::Aes, p_box: PBoxId::Transpose, key_schedule: KeySchedule::Expanded }
+    }
+
+    /// Таблицы для ключа `key`; от ключа зависит только `SBoxId::Keyed`,
+    /// `Custom` берутся из `tables`
+    pub fn to_spn(&self, key: &[u8; 32], tables: &CustomTables) -> Result<SpnParams, MetaError> {
+        let s_box = match self.s_box {
+            SBoxId::Aes => S_BOX,
+            SBoxId::Keyed => generate_s_box(key),
+            SBoxId::Custom => tables.s_box.ok_or(MetaError::MissingTables)?,
+        };
+        let p_box = match self.p_box {
+            PBoxId::Reverse => P_BOX,
+            PBoxId::Transpose => TRANSPOSE_P_BOX,
+            PBoxId::Custom => tables.p_box.ok_or(MetaError::MissingTables)?,
+        };
+        Ok(SpnParams::classic()
+            .with_rounds(self.rounds as usize)
+            .with_s_box(s_box)
+            .with_p_box(p_box)
+            .with_key_schedule(self.key_schedule))
+    }
+
+    fn to_bytes(self) -> [u8; CIPHER_PARAMS_LEN] {
+        [self.rounds, self.s_box as u8, self.p_box as u8, self.key_schedule as u8]
+    }
+
+    fn from_bytes(data: &[u8]) -> Result<Self, MetaError> {
+        if data.len() != CIPHER_PARAMS_LEN {
+            return Err(MetaError::BadCipherParams);
+        }
+        let rounds = data[0];
+        if rounds == 0 || rounds as usize > MAX_ROUNDS {
+            return Err(MetaError::BadCipherParams);
+        }
+        let s_box = match data[1] {
+            0 => SBoxId::Aes,
+            1 => SBoxId::Keyed,
+            2 => SBoxId::Custom,
+            id => return Err(MetaError::UnknownSBox(id)),
+        };
+        let p_box = match data[2] {
+            0 => PBoxId::Reverse,
+            1 => PBoxId::Transpose,
+            2 => PBoxId::Custom,
+            id => return Err(MetaError::UnknownPBox(id)),
+        };
+        let key_schedule = match data[3] {
+            0 => KeySchedule::Split,
+            1 => KeySchedule::Expanded,
+            id => return Err(MetaError::UnknownKeySchedule(id)),
+        };
+        Ok(CipherParams { rounds, s_box, p_box, key_schedule })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MetaError {
+    TooShort,
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownCipher(u8),
+    UnknownMode(u8),
+    UnknownKdf(u8),
+    UnknownFlags(u8),
+    MissingFlags(u8),
+    BadKdfParams,
+    BadHeaderLength(usize),
+    BadCipherParams,
+    UnknownSBox(u8),
+    UnknownPBox(u8),
+    UnknownKeySchedule(u8),
+    BadFingerprint,
+    BadHeaderTag,
+    MissingTables,
+}
+
+impl fmt::Display for MetaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooShort => write!(f, "Invalid metadata length"),
+            Self::BadMagic => write!(f, "Not an encrypted file (bad magic)"),
+            Self::UnsupportedVersion(v) => write!(f, "Unsupported format version {}", v),
+            Self::UnknownCipher(id) => write!(f, "Unknown cipher id {}", id),
+            Self::UnknownMode(id) => write!(f, "Unknown mode id {}", id),
+            Self::UnknownKdf(id) => write!(f, "Unknown KDF id {}", id),
+            Self::UnknownFlags(flags) => write!(f, "Unknown header flags {:#04x}", flags),
+            Self::MissingFlags(flags) => write!(f, "Required header flags {:#04x} are missing", flags),
+            Self::BadKdfParams => write!(f, "Invalid KDF parameters"),
+            Self::BadHeaderLength(len) => write!(f, "Inconsistent header length {}", len),
+            Self::BadCipherParams => write!(f, "Invalid cipher parameters"),
+            Self::UnknownSBox(id) => write!(f, "Unknown S-box id {}", id),
+            Self::UnknownPBox(id) => write!(f, "Unknown P-box id {}", id),
+            Self::UnknownKeySchedule(id) => write!(f, "Unknown key schedule id {}", id),
+            Self::BadFingerprint => write!(f, "Invalid table fingerprint section"),
+            Self::BadHeaderTag => write!(f, "Invalid header tag section"),
+            Self::MissingTables => write!(f, "File uses custom S-box/P-box tables that were not supplied"),
+        }
+    }
+}
+
+impl std::error::Error for MetaError {}
+
+impl CipherId {
+    fn from_u8(id: u8) -> Result<Self, MetaError> {
+        match id {
+            1 => Ok(Self::Spn128),
+            _ => Err(MetaError::UnknownCipher(id)),
+        }
+    }
+}
+
+impl ModeId {
+    fn from_u8(id: u8) -> Result<Self, MetaError> {
+        match id {
+            1 => Ok(Self::CtrHmac),
+            2 => Ok(Self::CbcHmac),
+            3 => Ok(Self::CfbHmac),
+            4 => Ok(Self::Cfb8Hmac),
+            5 => Ok(Self::OfbHmac),
+            _ => Err(MetaError::UnknownMode(id)),
+        }
+    }
+}
+
+impl KdfId {
+    fn from_u8(id: u8) -> Result<Self, MetaError> {
+        match id {
+            1 => Ok(Self::Billiard),
+            2 => Ok(Self::Pbkdf2),
+            3 => Ok(Self::Scrypt),
+            _ => Err(MetaError::UnknownKdf(id)),
+        }
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub struct Metadata {
+    pub version: u8,
+    pub cipher: CipherId,
+    pub mode: ModeId,
+    pub kdf: KdfId,
+    pub kdf_params: Vec<u8>,
+    pub flags: u8,
+    pub cipher_params: CipherParams,
+    pub table_fingerprint: Option<[u8; 32]>,
+    pub header_tag: Option<[u8; HEADER_TAG_LEN]>,
     pub salt: [u8; 32],
     pub iv: [u8; 16],
 }
 
+impl Default for Metadata {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Metadata {
-    /// Generate new metadata with random salt and IV (nonce + counter)    
+    /// Generate new metadata with random salt and IV (nonce + counter)
     pub fn new() -> Self {
+        Self::for_mode(ModeId::CtrHmac)
+    }
+
+    /// Для CTR случайна только nonce-часть IV (8 байт), счётчик начинается
+    /// с нуля; для остальных режимов IV должен быть непредсказуемым целиком
+    pub fn for_mode(mode: ModeId) -> Self {
         let mut salt = [0u8; 32];
         let mut iv = [0u8; 16];
-        
+
         let mut rng = RCTMPrng::from_entropy().expect("Failed to initialize CSPRNG");
         rng.fill_bytes(&mut salt);
-        rng.fill_bytes(&mut iv[..12]);
-        
-        Metadata { salt, iv }
+        match mode {
+            ModeId::CtrHmac => rng.fill_bytes(&mut iv[..8]),
+            _ => rng.fill_bytes(&mut iv),
+        }
+
+        Metadata {
+            version: CURRENT_VERSION,
+            cipher: CipherId::Spn128,
+            mode,
+            kdf: KdfId::Billiard,
+            kdf_params: Vec::new(),
+            flags: FLAG_COUNTER64 | FLAG_HEADER_TAG,
+            cipher_params: CipherParams::CLASSIC,
+            table_fingerprint: None,
+            header_tag: Some([0u8; HEADER_TAG_LEN]),
+            salt,
+            iv,
+        }
+    }
+
+    /// Заголовок файла v0: только соль и IV, ключ мог быть выведен без соли
+    pub fn legacy(salt: [u8; 32], iv: [u8; 16]) -> Self {
+        Metadata {
+            version: 0,
+            cipher: CipherId::Spn128,
+            mode: ModeId::CtrHmac,
+            kdf: KdfId::BilliardUnsalted,
+            kdf_params: Vec::new(),
+            flags: 0,
+            cipher_params: CipherParams::CLASSIC,
+            table_fingerprint: None,
+            header_tag: None,
+            salt,
+            iv,
+        }
+    }
+
+    pub fn set_kdf(&mut self, params: KdfParams) {
+        self.kdf = params.id();
+        self.kdf_params = params.to_bytes();
+    }
+
+    /// Отпечаток пользовательских таблиц, которыми зашифрован файл
+    pub fn set_table_fingerprint(&mut self, fingerprint: Option<[u8; 32]>) {
+        self.table_fingerprint = fingerprint;
+        if fingerprint.is_some() {
+            self.flags |= FLAG_TABLE_FINGERPRINT;
+        } else {
+            self.flags &= !FLAG_TABLE_FINGERPRINT;
+        }
+    }
+
+    /// Тег заголовка, вычисленный по уже заполненным остальным полям
+    pub fn set_header_tag(&mut self, tag: [u8; HEADER_TAG_LEN]) {
+        self.header_tag = Some(tag);
+    }
+
+    /// Байты, которые покрывает тег заголовка: заголовок с обнулённым полем тега
+    pub fn header_tag_input(&self) -> Vec<u8> {
+        let mut copy = Metadata { kdf_params: self.kdf_params.clone(), ..*self };
+        if copy.header_tag.is_some() {
+            copy.header_tag = Some([0u8; HEADER_TAG_LEN]);
+        }
+        copy.to_bytes()
+    }
+
+    /// Неклассические параметры записываются в заголовок отдельной секцией
+    pub fn set_cipher_params(&mut self, params: CipherParams) {
+        self.cipher_params = params;
+        if params == CipherParams::CLASSIC {
+            self.flags &= !FLAG_CIPHER_PARAMS;
+        } else {
+            self.flags |= FLAG_CIPHER_PARAMS;
+        }
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.version == 0
     }
-    pub fn increment_counter(&mut self) {
-        let counter_bytes = &mut self.iv[12..16];
-        let mut counter = u32::from_be_bytes(counter_bytes.try_into().unwrap());
-        counter += 1;
-        counter_bytes.copy_from_slice(&counter.to_be_bytes());
+
+    /// Раскладка счётчика CTR: 32-битная только у v0
+    pub fn counter_layout(&self) -> CounterLayout {
+        if self.is_legacy() {
+            CounterLayout::Legacy32
+        } else {
+            CounterLayout::Wide64
+        }
+    }
+
+    /// Увеличивает счётчик в IV; переполнение — ошибка, а не заворот
+    pub fn increment_counter(&mut self) -> Result<(), &'static str> {
+        let start = match self.counter_layout() {
+            CounterLayout::Legacy32 => 12,
+            CounterLayout::Wide64 => 8,
+        };
+        // Счётчик big-endian: младший байт, который ещё можно увеличить, и нули за ним
+        let counter = &mut self.iv[start..];
+        let pos = counter.iter().rposition(|&b| b != 0xff).ok_or("IV counter overflow")?;
+        counter[pos] += 1;
+        counter[pos + 1..].fill(0);
+        Ok(())
     }
 
-    /// Serialize metadata to bytes (salt || iv)
+    /// Nonce-часть IV (первые 12 байт) — для режимов со своим счётчиком, например GCM
+    pub fn nonce(&self) -> [u8; 12] {
+        *arrayref::array_ref!(self.iv, 0, 12)
+    }
+
+    /// Длина сериализованного заголовка
+    pub fn header_len(&self) -> usize {
+        if self.is_legacy() {
+            LEGACY_LEN
+        } else {
+            FIXED_LEN + self.kdf_params.len() + self.sections_len() + SALT_LEN + IV_LEN
+        }
+    }
+
+    fn sections_len(&self) -> usize {
+        let mut len = 0;
+        if self.flags & FLAG_CIPHER_PARAMS != 0 {
+            len += 1 + CIPHER_PARAMS_LEN;
+        }
+        if self.flags & FLAG_TABLE_FINGERPRINT != 0 {
+            len += 1 + FINGERPRINT_LEN;
+        }
+        if self.flags & FLAG_HEADER_TAG != 0 {
+            len += 1 + HEADER_TAG_LEN;
+        }
+        len
+    }
+
+    /// Serialize metadata to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(48);
+        let mut bytes = Vec::with_capacity(self.header_len());
+        if !self.is_legacy() {
+            bytes.extend_from_slice(&MAGIC);
+            bytes.push(self.version);
+            bytes.extend_from_slice(&(self.header_len() as u16).to_be_bytes());
+            bytes.push(self.cipher as u8);
+            bytes.push(self.mode as u8);
+            bytes.push(self.kdf as u8);
+            bytes.push(self.flags);
+            bytes.push(self.kdf_params.len() as u8);
+            bytes.extend_from_slice(&self.kdf_params);
+            if self.flags & FLAG_CIPHER_PARAMS != 0 {
+                bytes.push(CIPHER_PARAMS_LEN as u8);
+                bytes.extend_from_slice(&self.cipher_params.to_bytes());
+            }
+            if let Some(fingerprint) = &self.table_fingerprint {
+                bytes.push(FINGERPRINT_LEN as u8);
+                bytes.extend_from_slice(fingerprint);
+            }
+            if let Some(tag) = &self.header_tag {
+                bytes.push(HEADER_TAG_LEN as u8);
+                bytes.extend_from_slice(tag);
+            }
+        }
         bytes.extend_from_slice(&self.salt);
         bytes.extend_from_slice(&self.iv);
         bytes
     }
 
-    /// Deserialize metadata from bytes
-    pub fn from_bytes(data: &[u8]) -> Result<Self, &'static str> {
-        if data.len() != 48 {
-            return Err("Invalid metadata length");
+    /// Deserialize metadata from the beginning of `data`.
+    ///
+    /// Данные без магии считаются заголовком v0. Длина заголовка — `header_len()`.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MetaError> {
+        if !data.starts_with(&MAGIC) {
+            if data.len() < LEGACY_LEN {
+                return Err(MetaError::TooShort);
+            }
+            let mut salt = [0u8; 32];
+            let mut iv = [0u8; 16];
+            salt.copy_from_slice(&data[0..32]);
+            iv.copy_from_slice(&data[32..48]);
+            return Ok(Metadata::legacy(salt, iv));
+        }
+
+        if data.len() < FIXED_LEN {
+            return Err(MetaError::TooShort);
+        }
+        let version = data[8];
+        if version != CURRENT_VERSION {
+            return Err(MetaError::UnsupportedVersion(version));
+        }
+        let header_len = u16::from_be_bytes([data[9], data[10]]) as usize;
+        let cipher = CipherId::from_u8(data[11])?;
+        let mode = ModeId::from_u8(data[12])?;
+        let kdf = KdfId::from_u8(data[13])?;
+        let flags = data[14];
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(MetaError::UnknownFlags(flags));
+        }
+        if flags & REQUIRED_FLAGS != REQUIRED_FLAGS {
+            return Err(MetaError::MissingFlags(!flags & REQUIRED_FLAGS));
+        }
+        let params_len = data[15] as usize;
+        let mut pos = FIXED_LEN;
+        let kdf_params = data.get(pos..pos + params_len).ok_or(MetaError::TooShort)?.to_vec();
+        KdfParams::from_bytes(kdf, &kdf_params)?;
+        pos += params_len;
+
+        let mut cipher_params = CipherParams::CLASSIC;
+        if flags & FLAG_CIPHER_PARAMS != 0 {
+            let section = read_section(data, &mut pos)?;
+            cipher_params = CipherParams::from_bytes(section)?;
+        }
+        let mut table_fingerprint = None;
+        if flags & FLAG_TABLE_FINGERPRINT != 0 {
+            let section = read_section(data, &mut pos)?;
+            table_fingerprint = Some(section.try_into().map_err(|_| MetaError::BadFingerprint)?);
+        }
+        let mut header_tag = None;
+        if flags & FLAG_HEADER_TAG != 0 {
+            let section = read_section(data, &mut pos)?;
+            header_tag = Some(section.try_into().map_err(|_| MetaError::BadHeaderTag)?);
+        }
+
+        let expected_len = pos + SALT_LEN + IV_LEN;
+        if header_len != expected_len {
+            return Err(MetaError::BadHeaderLength(header_len));
+        }
+        if data.len() < header_len {
+            return Err(MetaError::TooShort);
         }
 
         let mut salt = [0u8; 32];
+        salt.copy_from_slice(&data[pos..pos + SALT_LEN]);
+        pos += SALT_LEN;
         let mut iv = [0u8; 16];
-        
-        salt.copy_from_slice(&data[0..32]);
-        iv.copy_from_slice(&data[32..48]);
+        iv.copy_from_slice(&data[pos..pos + IV_LEN]);
 
-        Ok(Metadata { salt, iv })
+        Ok(Metadata { version, cipher, mode, kdf, kdf_params, flags, cipher_params, table_fingerprint, header_tag, salt, iv })
     }
-}
\ No newline at end of file
+}
+
+/// Читает секцию `len || данные` и сдвигает `pos` за её конец
+fn read_section<'a>(data: &'a [u8], pos: &mut usize) -> Result<&'a [u8], MetaError> {
+    let len = *data.get(*pos).ok_or(MetaError::TooShort)? as usize;
+    let section = data.get(*pos + 1..*pos + 1 + len).ok_or(MetaError::TooShort)?;
+    *pos += 1 + len;
+    Ok(section)
+}
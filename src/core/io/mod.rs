@@ -3,4 +3,7 @@ pub mod file;
 pub mod dir;
 pub mod meta;
 pub mod folder;
+pub mod stream;
+pub mod tables;
+#[allow(non_snake_case)]
 pub mod RCTMPrng;
\ No newline at end of file
@@ -0,0 +1,107 @@
+//! Потоковая обработка: данные идут через буфер фиксированного размера,
+//! поэтому потребление памяти не зависит от размера файла
+use std::io::{self, Read, Write};
+use crate::core::crypto::cipher::BLOCK_SIZE;
+use crate::core::crypto::hmac::HmacSha256;
+use crate::core::crypto::modes::{CipherMode, pkcs7_pad, pkcs7_unpad};
+use crate::core::crypto::secret::Secret;
+
+/// Размер буфера; кратен размеру блока, чтобы счётчик CTR шёл без разрывов
+pub const BUFFER_SIZE: usize = 1 << 20;
+
+/// Читает, пока буфер не заполнится или не кончатся данные
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+fn invalid_data(e: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Шифрует всё содержимое `reader` в `writer`, добавляя шифртекст в `mac`.
+/// Для режимов с дополнением последний буфер дополняется по PKCS#7.
+/// Возвращает количество байт открытого текста
+pub fn encrypt_stream<R: Read, W: Write, M: CipherMode + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    mode: &mut M,
+    mac: &mut HmacSha256,
+) -> io::Result<u64> {
+    // Буфер содержит открытый текст и затирается при выходе
+    let mut buf = Secret::new(vec![0u8; BUFFER_SIZE]);
+    let mut total = 0u64;
+    loop {
+        let n = read_full(reader, &mut buf)?;
+        total += n as u64;
+        let last = n < buf.len();
+        if last {
+            buf.truncate(n);
+            if mode.padded() {
+                pkcs7_pad(&mut buf);
+            }
+        }
+        mode.encrypt(&mut buf).map_err(io::Error::other)?;
+        mac.update(&buf);
+        writer.write_all(&buf)?;
+        if last {
+            break;
+        }
+    }
+    Ok(total)
+}
+
+/// Прогоняет ровно `len` байт из `reader` через `mac`
+pub fn mac_stream<R: Read>(reader: &mut R, len: u64, mac: &mut HmacSha256) -> io::Result<()> {
+    let mut buf = vec![0u8; BUFFER_SIZE];
+    let mut remaining = len;
+    while remaining > 0 {
+        let want = remaining.min(buf.len() as u64) as usize;
+        reader.read_exact(&mut buf[..want])?;
+        mac.update(&buf[..want]);
+        remaining -= want as u64;
+    }
+    Ok(())
+}
+
+/// Расшифровывает ровно `len` байт из `reader` в `writer`; дополнение,
+/// если оно есть, снимается с последнего буфера. `mac` получает шифртекст
+/// до расшифрования: к ошибке дополнения он уже содержит все `len` байт
+pub fn decrypt_stream<R: Read, W: Write, M: CipherMode + ?Sized>(
+    reader: &mut R,
+    writer: &mut W,
+    len: u64,
+    mode: &mut M,
+    mut mac: Option<&mut HmacSha256>,
+) -> io::Result<()> {
+    if mode.padded() && (len == 0 || !len.is_multiple_of(BLOCK_SIZE as u64)) {
+        return Err(invalid_data("Invalid ciphertext length"));
+    }
+    let mut buf = Secret::new(vec![0u8; BUFFER_SIZE]);
+    let mut remaining = len;
+    while remaining > 0 {
+        let want = remaining.min(buf.len() as u64) as usize;
+        reader.read_exact(&mut buf[..want])?;
+        if let Some(mac) = mac.as_deref_mut() {
+            mac.update(&buf[..want]);
+        }
+        mode.decrypt(&mut buf[..want]).map_err(invalid_data)?;
+        remaining -= want as u64;
+
+        let plain_len = if remaining == 0 && mode.padded() {
+            pkcs7_unpad(&buf[..want]).map_err(invalid_data)?
+        } else {
+            want
+        };
+        writer.write_all(&buf[..plain_len])?;
+    }
+    Ok(())
+}
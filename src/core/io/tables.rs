@@ -0,0 +1,110 @@
+//! Пользовательские S-box и P-box из файлов.
+//!
+//! Файл таблицы — это список значений в одном из двух видов:
+//!
+//! * JSON-массив десятичных чисел: `[99, 124, 119, ...]`;
+//! * hex-текст: байты через пробелы, запятые или переводы строк, с префиксом `0x`
+//!   или без него, либо сплошной строкой (`637c777b...`). `#` начинает комментарий.
+//!
+//! S-box — 256 значений, P-box — 128 значений (номера битов), оба должны быть перестановками.
+use std::fs;
+use std::path::Path;
+use crate::core::crypto::sha256::Sha256;
+
+/// Таблицы, заданные пользователем вместо встроенных
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CustomTables {
+    pub s_box: Option<[u8; 256]>,
+    pub p_box: Option<[usize; 128]>,
+}
+
+impl CustomTables {
+    pub fn is_empty(&self) -> bool {
+        self.s_box.is_none() && self.p_box.is_none()
+    }
+
+    /// Отпечаток заданных таблиц; записывается в заголовок файла
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"spn tables");
+        match &self.s_box {
+            Some(s_box) => hasher.update(&[1]).update(s_box),
+            None => hasher.update(&[0]),
+        };
+        match &self.p_box {
+            Some(p_box) => hasher.update(&[1]).update(&p_box.map(|v| v as u8)),
+            None => hasher.update(&[0]),
+        };
+        hasher.finalize()
+    }
+}
+
+/// Разбирает значения таблицы из JSON-массива или hex-текста
+pub fn parse_table(text: &str) -> Result<Vec<usize>, String> {
+    let trimmed = text.trim();
+    if let Some(inner) = trimmed.strip_prefix('[') {
+        let inner = inner.strip_suffix(']').ok_or("Unterminated JSON array")?;
+        if inner.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        return inner.split(',')
+            .map(|v| v.trim().parse::<usize>().map_err(|_| format!("Invalid table value '{}'", v.trim())))
+            .collect();
+    }
+
+    let mut values = Vec::new();
+    for line in trimmed.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        for token in line.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()) {
+            let digits = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).unwrap_or(token);
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(format!("Invalid hex value '{}'", token));
+            }
+            if digits.len() > 2 && !digits.len().is_multiple_of(2) {
+                return Err(format!("Odd number of hex digits in '{}'", token));
+            }
+            for start in (0..digits.len()).step_by(2) {
+                let pair = &digits[start..digits.len().min(start + 2)];
+                values.push(usize::from_str_radix(pair, 16)
+                    .map_err(|_| format!("Invalid hex value '{}'", token))?);
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Проверяет, что `values` — перестановка чисел `0..N`
+fn to_permutation<const N: usize>(values: Vec<usize>, name: &str) -> Result<[usize; N], String> {
+    let table: [usize; N] = values.try_into()
+        .map_err(|v: Vec<usize>| format!("{} must have {} entries, got {}", name, N, v.len()))?;
+    let mut seen = [false; N];
+    for &v in &table {
+        if v >= N {
+            return Err(format!("{} value {} is out of range", name, v));
+        }
+        if std::mem::replace(&mut seen[v], true) {
+            return Err(format!("{} is not a permutation: value {} repeats", name, v));
+        }
+    }
+    Ok(table)
+}
+
+pub fn parse_s_box(text: &str) -> Result<[u8; 256], String> {
+    to_permutation::<256>(parse_table(text)?, "S-box").map(|t| t.map(|v| v as u8))
+}
+
+pub fn parse_p_box(text: &str) -> Result<[usize; 128], String> {
+    to_permutation::<128>(parse_table(text)?, "P-box")
+}
+
+pub fn load_s_box(path: &Path) -> Result<[u8; 256], String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Error reading S-box file '{}': {}", path.display(), e))?;
+    parse_s_box(&text)
+}
+
+pub fn load_p_box(path: &Path) -> Result<[usize; 128], String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Error reading P-box file '{}': {}", path.display(), e))?;
+    parse_p_box(&text)
+}
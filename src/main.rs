@@ -1,8 +1,10 @@
 //! CLI entry point
-use crypto_app::cli::{self, Args};
+use crypto_app::cli;
 use clap::Parser;
 use crypto_app::core::io::{file, folder};
-use std::path::{PathBuf, Path};
+use crypto_app::core::io::file::{EncryptOptions, DecryptOptions};
+use crypto_app::core::crypto::secret::Secret;
+use std::path::Path;
 use libc::{time_t, time, localtime_r, strftime, tm};
 use std::ffi::CStr;
 use std::fs;
@@ -17,7 +19,7 @@ fn get_timestamp() -> String {
     let mut tm_struct: tm = unsafe { std::mem::zeroed() };
     unsafe { localtime_r(&raw_time, &mut tm_struct) };
     
-    let mut buffer = [0i8; 64];
+    let mut buffer = [0 as libc::c_char; 64];
     unsafe {
         strftime(
             buffer.as_mut_ptr(),
@@ -56,7 +58,14 @@ fn clean_old_logs() -> std::io::Result<()> {
     Ok(())
 }
 
-fn write_session_log(command: &str, status: &str, input: &PathBuf, output: &PathBuf, error: Option<String>) {
+/// Файлы v0 не содержат тега: неверный пароль или подмена дадут мусор, а не ошибку
+fn warn_legacy(legacy: bool) {
+    if legacy {
+        eprintln!("⚠️ --legacy: файлы старого формата расшифровываются без проверки пароля и целостности");
+    }
+}
+
+fn write_session_log(command: &str, status: &str, input: &Path, output: &Path, error: Option<String>) {
     let _ = ensure_log_dir();
     let _ = clean_old_logs();
     
@@ -74,45 +83,113 @@ fn write_session_log(command: &str, status: &str, input: &PathBuf, output: &Path
 fn main() {
     let args = cli::Args::parse();
     
-    match &args.command {
-        cli::Command::EncryptFile { password, input, output } => {
-            if let Err(e) = file::encrypt_file(input, output, password) {
+    // Команда забирается по значению, чтобы пароль затёрся после использования
+    match args.command {
+        cli::Command::EncryptFile { password, input, output, mode, preset, keyed_s_box, s_box, p_box, kdf } => {
+            let password = Secret::new(password);
+            let result = cli::load_tables(s_box.as_deref(), p_box.as_deref()).and_then(|tables| {
+                let params = cli::cipher_params(preset, keyed_s_box);
+                let kdf = kdf.params()?;
+                let options = EncryptOptions { mode: mode.into(), params, tables, kdf };
+                file::encrypt_file_with(&input, &output, &password, &options)
+            });
+            if let Err(e) = result {
                 eprintln!("❌Ошибка шифрования файла: {}💧", e);
-                write_session_log("EncryptFile", "FAILURE", input, output, Some(e.to_string()));
+                write_session_log("EncryptFile", "FAILURE", &input, &output, Some(e.to_string()));
             } else {
                 println!("✅ Файл успешно зашифрован и сохранен в: {}", output.display());
-                write_session_log("EncryptFile", "SUCCESS", input, output, None);
+                write_session_log("EncryptFile", "SUCCESS", &input, &output, None);
             }
         }
         
-        cli::Command::DecryptFile { password, input, output } => {
-            if let Err(e) = file::decrypt_file(input, output, password) {
+        cli::Command::DecryptFile { password, input, output, range, s_box, p_box, legacy } => {
+            let password = Secret::new(password);
+            warn_legacy(legacy);
+            let result = cli::load_tables(s_box.as_deref(), p_box.as_deref()).and_then(|tables| {
+                let options = DecryptOptions { tables, allow_legacy: legacy };
+                match range {
+                    Some(range) => file::decrypt_range(&input, &output, &password, range.start, range.len, &options),
+                    None => file::decrypt_file_with(&input, &output, &password, &options),
+                }
+            });
+            if let Err(e) = result {
                 eprintln!("❌Ошибка дешифрования файла: {}", e);
-                write_session_log("DecryptFile", "FAILURE", input, output, Some(e.to_string()));
+                write_session_log("DecryptFile", "FAILURE", &input, &output, Some(e.to_string()));
             } else {
                 println!("✅ Файл успешно дешифрован и сохранен в: {}", output.display());
-                write_session_log("DecryptFile", "SUCCESS", input, output, None);
+                write_session_log("DecryptFile", "SUCCESS", &input, &output, None);
             }
         }
         
-        cli::Command::EncryptDir { password, input, output } => {
-            if let Err(e) = folder::encrypt_directory(input, output, password) {
+        cli::Command::EncryptDir { password, input, output, mode, preset, keyed_s_box, s_box, p_box, kdf } => {
+            let password = Secret::new(password);
+            let result = cli::load_tables(s_box.as_deref(), p_box.as_deref()).and_then(|tables| {
+                let params = cli::cipher_params(preset, keyed_s_box);
+                let kdf = kdf.params()?;
+                let options = EncryptOptions { mode: mode.into(), params, tables, kdf };
+                folder::encrypt_directory_with(&input, &output, &password, &options)
+            });
+            if let Err(e) = result {
                 eprintln!("Ошибка шифрования директории: {}", e);
-                write_session_log("EncryptDir", "FAILURE", input, output, Some(e.to_string()));
+                write_session_log("EncryptDir", "FAILURE", &input, &output, Some(e.to_string()));
             } else {
                 println!("✅ Директория успешно зашифрована и сохранена в: {}", output.display());
-                write_session_log("EncryptDir", "SUCCESS", input, output, None);
+                write_session_log("EncryptDir", "SUCCESS", &input, &output, None);
             }
         }
         
-        cli::Command::DecryptDir { password, input, output } => {
-            if let Err(e) = folder::decrypt_directory(input, output, password) {
+        cli::Command::DecryptDir { password, input, output, s_box, p_box, legacy } => {
+            let password = Secret::new(password);
+            warn_legacy(legacy);
+            let result = cli::load_tables(s_box.as_deref(), p_box.as_deref()).and_then(|tables| {
+                folder::decrypt_directory_with(&input, &output, &password, &DecryptOptions { tables, allow_legacy: legacy })
+            });
+            if let Err(e) = result {
                 eprintln!("Ошибка дешифрования директории: {}", e);
-                write_session_log("DecryptDir", "FAILURE", input, output, Some(e.to_string()));
+                write_session_log("DecryptDir", "FAILURE", &input, &output, Some(e.to_string()));
             } else {
                 println!("✅ Директория успешно дешифрована и сохранена в: {}", output.display());
-                write_session_log("DecryptDir", "SUCCESS", input, output, None);
+                write_session_log("DecryptDir", "SUCCESS", &input, &output, None);
+            }
+        }
+
+        cli::Command::Mac { key, key_file, input } => {
+            let key = key.map(Secret::new);
+            let result = cli::load_mac_key(key.as_deref().map(String::as_str), key_file.as_deref())
+                .and_then(|key| file::mac_file(&input, &key));
+            match result {
+                Ok(tag) => {
+                    println!("{}", cli::to_hex(&tag));
+                    write_session_log("Mac", "SUCCESS", &input, Path::new("-"), None);
+                }
+                Err(e) => {
+                    eprintln!("❌Ошибка вычисления MAC: {}", e);
+                    write_session_log("Mac", "FAILURE", &input, Path::new("-"), Some(e));
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        cli::Command::MacVerify { key, key_file, input, tag } => {
+            let key = key.map(Secret::new);
+            let result = cli::load_mac_key(key.as_deref().map(String::as_str), key_file.as_deref())
+                .and_then(|key| cli::from_hex(&tag).and_then(|tag| file::verify_file_mac(&input, &key, &tag)));
+            match result {
+                Ok(true) => {
+                    println!("✅ MAC совпадает");
+                    write_session_log("MacVerify", "SUCCESS", &input, Path::new("-"), None);
+                }
+                Ok(false) => {
+                    eprintln!("❌MAC не совпадает");
+                    write_session_log("MacVerify", "FAILURE", &input, Path::new("-"), Some("MAC mismatch".into()));
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("❌Ошибка проверки MAC: {}", e);
+                    write_session_log("MacVerify", "FAILURE", &input, Path::new("-"), Some(e));
+                    std::process::exit(1);
+                }
             }
         }
     }
-}
\ No newline at end of file
+}
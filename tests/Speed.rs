@@ -49,4 +49,19 @@ fn encryption_speed_test() {
         TEST_FILE_SIZE_MB as f64 / encrypt_duration.as_secs_f64());
     println!("Decryption speed: {:.2} MB/s\n",
         TEST_FILE_SIZE_MB as f64 / decrypt_duration.as_secs_f64());
-}
\ No newline at end of file
+}
+/// Скорость одной гаммы CTR, без вывода ключа и HMAC
+#[test]
+fn keystream_speed_test() {
+    use crypto_app::core::crypto::cipher::{Cipher, SpnParams};
+
+    let mut buf = vec![0u8; TEST_FILE_SIZE_MB * 1024 * 1024];
+    for (name, params) in [("classic-2r", SpnParams::classic()), ("hardened-10r", SpnParams::hardened(10))] {
+        let cipher = Cipher::with_params([7u8; 32], &params).expect("Invalid params");
+        let start = Instant::now();
+        cipher.apply_ctr_at(&[0u8; 16], 0, &mut buf).expect("CTR failed");
+        let duration = start.elapsed();
+        println!("Keystream {} ({:?}): {:.2} MB/s", name, cipher.backend(),
+            TEST_FILE_SIZE_MB as f64 / duration.as_secs_f64());
+    }
+}
@@ -0,0 +1,78 @@
+use crypto_app::core::crypto::hmac::HmacSha256;
+use crypto_app::core::io::file::{encrypt_file, decrypt_file};
+use hex_literal::hex;
+use tempfile::TempDir;
+use std::fs;
+
+#[test]
+fn hmac_rfc4231_case_2() {
+    let mut mac = HmacSha256::new(b"Jefe");
+    mac.update(b"what do ya want for nothing?");
+    assert_eq!(
+        mac.finalize(),
+        hex!("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+    );
+}
+
+#[test]
+fn wrong_password_is_rejected_without_output() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.txt");
+    let encrypted = dir.path().join("plain.enc");
+    let decrypted = dir.path().join("plain.dec");
+    fs::write(&plain, b"attack at dawn").unwrap();
+
+    encrypt_file(&plain, &encrypted, "right_pass").unwrap();
+
+    let err = decrypt_file(&encrypted, &decrypted, "wrong_pass").unwrap_err();
+    assert!(err.contains("Authentication failed"), "unexpected error: {}", err);
+    assert!(!decrypted.exists());
+}
+
+#[test]
+fn tampered_ciphertext_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.txt");
+    let encrypted = dir.path().join("plain.enc");
+    let decrypted = dir.path().join("plain.dec");
+    fs::write(&plain, vec![0x42u8; 1000]).unwrap();
+
+    encrypt_file(&plain, &encrypted, "password").unwrap();
+
+    let original = fs::read(&encrypted).unwrap();
+    // Header byte, ciphertext byte and tag byte
+    for pos in [0, original.len() / 2, original.len() - 1] {
+        let mut tampered = original.clone();
+        tampered[pos] ^= 0x01;
+        fs::write(&encrypted, &tampered).unwrap();
+
+        let err = decrypt_file(&encrypted, &decrypted, "password").unwrap_err();
+        assert!(err.contains("Authentication failed"), "unexpected error: {}", err);
+        assert!(!decrypted.exists());
+    }
+
+    // Truncation must be caught too
+    fs::write(&encrypted, &original[..original.len() - 5]).unwrap();
+    assert!(decrypt_file(&encrypted, &decrypted, "password").is_err());
+    assert!(!decrypted.exists());
+}
+
+#[test]
+fn in_place_round_trip_keeps_file_on_failure() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("data.bin");
+    let data: Vec<u8> = (0..5000u32).map(|i| (i * 7) as u8).collect();
+    fs::write(&path, &data).unwrap();
+
+    encrypt_file(&path, &path, "password").unwrap();
+    let encrypted = fs::read(&path).unwrap();
+    assert_ne!(encrypted, data);
+
+    // Failed decryption leaves the input untouched and no temp files behind
+    assert!(decrypt_file(&path, &path, "wrong").is_err());
+    assert_eq!(fs::read(&path).unwrap(), encrypted);
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    decrypt_file(&path, &path, "password").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), data);
+}
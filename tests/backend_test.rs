@@ -0,0 +1,132 @@
+use crypto_app::core::crypto::backend::{Backend, BATCH};
+use crypto_app::core::crypto::s_box::{generate_s_box, S_BOX};
+use crypto_app::core::crypto::cipher::{Cipher, SpnParams};
+
+const KEY: [u8; 32] = *b"0123456789abcdef0123456789ABCDEF";
+
+#[test]
+fn simd_backend_is_detected() {
+    let detected = Backend::detect();
+    assert_eq!(Cipher::new(KEY).backend(), detected);
+    assert!(Backend::available().contains(&Backend::Scalar));
+    assert_eq!(Backend::available().last(), Some(&detected));
+    #[cfg(target_arch = "x86_64")]
+    assert_ne!(detected, Backend::Scalar);
+    #[cfg(target_arch = "aarch64")]
+    assert_eq!(detected, Backend::Neon);
+}
+
+#[test]
+fn xor_matches_scalar() {
+    for seed in 0..=255u8 {
+        let a: [u8; 16] = std::array::from_fn(|i| seed.wrapping_mul(i as u8 + 3));
+        let b: [u8; 16] = std::array::from_fn(|i| seed ^ (i as u8).wrapping_mul(29));
+        let expected: [u8; 16] = std::array::from_fn(|i| a[i] ^ b[i]);
+        for backend in Backend::available() {
+            let mut buf = a;
+            backend.xor_block(&mut buf, &b);
+            assert_eq!(buf, expected, "{:?}", backend);
+        }
+    }
+}
+
+#[test]
+fn batch_substitution_matches_scalar() {
+    for s_box in [S_BOX, generate_s_box(&KEY)] {
+        // 8 блоков по 16 байт — каждое значение байта встречается ровно раз на две пачки
+        for half in 0..2u8 {
+            let input: [[u8; 16]; BATCH] = std::array::from_fn(|b| {
+                std::array::from_fn(|i| half * 128 + (b * 16 + i) as u8)
+            });
+            let mut expected = input;
+            Backend::Scalar.substitute_batch(&s_box, &mut expected);
+            assert!(expected.iter().flatten().zip(input.iter().flatten())
+                .all(|(&e, &x)| e == s_box[x as usize]));
+
+            for backend in Backend::available() {
+                let mut buf = input;
+                backend.substitute_batch(&s_box, &mut buf);
+                assert_eq!(buf, expected, "{:?}", backend);
+            }
+        }
+    }
+}
+
+/// Любой вариант безопасен и корректен, даже если его нет в `available`:
+/// без поддержки процессора он выполняется скалярно
+#[test]
+fn every_variant_matches_scalar() {
+    let all = [
+        Backend::Scalar,
+        #[cfg(target_arch = "x86_64")]
+        Backend::Sse2,
+        #[cfg(target_arch = "x86_64")]
+        Backend::Ssse3,
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2,
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon,
+    ];
+    let input: [[u8; 16]; BATCH] = std::array::from_fn(|b| std::array::from_fn(|i| (b * 31 + i * 7) as u8));
+    let mut expected = input;
+    Backend::Scalar.substitute_batch(&S_BOX, &mut expected);
+    for backend in all {
+        let mut buf = input;
+        backend.substitute_batch(&S_BOX, &mut buf);
+        assert_eq!(buf, expected, "{:?}", backend);
+
+        let mut block = input[0];
+        backend.xor_block(&mut block, &input[1]);
+        assert_eq!(block, std::array::from_fn(|i| input[0][i] ^ input[1][i]), "{:?}", backend);
+    }
+}
+
+#[test]
+fn forced_scalar_cipher_matches_simd() {
+    for params in [SpnParams::classic(), SpnParams::hardened(10)] {
+        let simd = Cipher::with_params(KEY, &params).unwrap();
+        let scalar = Cipher::with_params(KEY, &params).unwrap()
+            .with_backend(Backend::Scalar).unwrap();
+        assert_eq!(scalar.backend(), Backend::Scalar);
+
+        for seed in 0..64u8 {
+            let block = [seed; 16];
+            let mut a = block;
+            let mut b = block;
+            simd.encrypt_block(&mut a);
+            scalar.encrypt_block(&mut b);
+            assert_eq!(a, b);
+
+            simd.decrypt_block(&mut a);
+            scalar.decrypt_block(&mut b);
+            assert_eq!(a, block);
+            assert_eq!(b, block);
+        }
+
+        let data: Vec<u8> = (0..4099u32).map(|i| (i * 7) as u8).collect();
+        let iv = [0xa5u8; 16];
+        assert_eq!(simd.apply_ctr(&data, &iv).unwrap(), scalar.apply_ctr(&data, &iv).unwrap());
+    }
+}
+
+#[test]
+fn batched_keystream_matches_block_encryption() {
+    // Больше одной части для rayon, неполная пачка и неполный блок в конце
+    let len = 40 * 1024 + 5 * 16 + 7;
+    let iv = [0xfeu8; 16];
+    let first_block = 3u64;
+    for params in [SpnParams::classic(), SpnParams::hardened(10)] {
+        for backend in Backend::available() {
+            let cipher = Cipher::with_params(KEY, &params).unwrap().with_backend(backend).unwrap();
+            let mut buf = vec![0u8; len];
+            cipher.apply_ctr_at(&iv, first_block, &mut buf).unwrap();
+
+            let base = u128::from_be_bytes(iv) + first_block as u128;
+            for (i, chunk) in buf.chunks(16).enumerate() {
+                let mut block = (base + i as u128).to_be_bytes();
+                cipher.encrypt_block(&mut block);
+                assert_eq!(chunk, &block[..chunk.len()], "{:?}, block {}", backend, i);
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crypto_app::core::crypto::cipher::Cipher;
+use crypto_app::core::crypto::modes::{encrypt_cbc, decrypt_cbc, pkcs7_pad, pkcs7_unpad, Cbc, CipherMode};
+use crypto_app::core::io::file::{encrypt_file_with, decrypt_file, EncryptOptions};
+use crypto_app::core::io::meta::{Metadata, ModeId};
+use crypto_app::core::io::stream::BUFFER_SIZE;
+use tempfile::TempDir;
+use std::fs;
+
+fn sample(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 13 + 1) as u8).collect()
+}
+
+#[test]
+fn decrypt_block_inverts_encrypt_block() {
+    let cipher = Cipher::new(*b"0123456789abcdef0123456789ABCDEF");
+    for seed in 0..64u8 {
+        let original: [u8; 16] = core::array::from_fn(|i| seed.wrapping_mul(31).wrapping_add(i as u8 * 17));
+        let mut block = original;
+        cipher.encrypt_block(&mut block);
+        assert_ne!(block, original);
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, original);
+    }
+}
+
+#[test]
+fn cbc_chains_blocks() {
+    let cipher = Cipher::new([4u8; 32]);
+    let iv = [0x24u8; 16];
+    let data = sample(32);
+
+    let encrypted = encrypt_cbc(&cipher, &iv, &data);
+    assert_eq!(encrypted.len(), 48);
+
+    let mut first: [u8; 16] = data[..16].try_into().unwrap();
+    first.iter_mut().zip(iv.iter()).for_each(|(b, v)| *b ^= v);
+    cipher.encrypt_block(&mut first);
+    assert_eq!(&encrypted[..16], &first);
+
+    let mut second: [u8; 16] = data[16..32].try_into().unwrap();
+    second.iter_mut().zip(first.iter()).for_each(|(b, c)| *b ^= c);
+    cipher.encrypt_block(&mut second);
+    assert_eq!(&encrypted[16..32], &second);
+}
+
+#[test]
+fn cbc_roundtrip_all_tail_lengths() {
+    let cipher = Cipher::new([5u8; 32]);
+    let iv = [0x11u8; 16];
+    for len in 0..=48 {
+        let data = sample(len);
+        let encrypted = encrypt_cbc(&cipher, &iv, &data);
+        assert_eq!(encrypted.len(), (len / 16 + 1) * 16);
+        assert_eq!(decrypt_cbc(&cipher, &iv, &encrypted).unwrap(), data);
+    }
+}
+
+#[test]
+fn bad_padding_is_reported() {
+    let cipher = Cipher::new([6u8; 32]);
+    let iv = [0u8; 16];
+
+    // Блок без дополнения: последний байт 0 — недопустимое значение PKCS#7
+    let mut raw = [0u8; 16];
+    Cbc::new(&cipher, iv).encrypt(&mut raw).unwrap();
+    assert_eq!(decrypt_cbc(&cipher, &iv, &raw), Err("Invalid PKCS#7 padding"));
+
+    assert!(decrypt_cbc(&cipher, &iv, &raw[..15]).is_err());
+    assert!(decrypt_cbc(&cipher, &iv, &[]).is_err());
+}
+
+#[test]
+fn pkcs7_helpers() {
+    let mut data = b"YELLOW SUBMARINE".to_vec();
+    pkcs7_pad(&mut data);
+    assert_eq!(data.len(), 32);
+    assert!(data[16..].iter().all(|&b| b == 16));
+    assert_eq!(pkcs7_unpad(&data), Ok(16));
+
+    let mut bad = [4u8; 16];
+    bad[13] = 5;
+    assert!(pkcs7_unpad(&bad).is_err());
+    assert!(pkcs7_unpad(&[17u8; 16]).is_err());
+}
+
+#[test]
+fn cbc_file_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let options = EncryptOptions { mode: ModeId::CbcHmac, ..Default::default() };
+    for len in [0, 5, 16, 33, BUFFER_SIZE] {
+        let plain = dir.path().join(format!("{}.txt", len));
+        let encrypted = dir.path().join(format!("{}.enc", len));
+        let decrypted = dir.path().join(format!("{}.dec", len));
+        let data = sample(len);
+        fs::write(&plain, &data).unwrap();
+
+        encrypt_file_with(&plain, &encrypted, "password", &options).unwrap();
+        let header = Metadata::from_bytes(&fs::read(&encrypted).unwrap()).unwrap();
+        assert_eq!(header.mode, ModeId::CbcHmac);
+
+        decrypt_file(&encrypted, &decrypted, "password").unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), data, "length {}", len);
+    }
+}
@@ -26,7 +26,7 @@ fn test_cipher_nist_full() {
     rng.fill_bytes(&mut plaintext[..]);
     
     // Шифрование
-    let ciphertext = cipher.encrypt(&plaintext, &iv);
+    let ciphertext = cipher.encrypt(&plaintext, &iv).unwrap();
     
     let data = BitsData::from_binary(ciphertext);
     let mut passed = 0;
@@ -0,0 +1,91 @@
+// Файлы, созданные предыдущими версиями формата, должны расшифровываться
+use crypto_app::core::crypto::{cipher::{Cipher, CounterLayout}, keygen::derive_key};
+use crypto_app::core::io::{file::{decrypt_file, decrypt_file_with, DecryptOptions}, meta::Metadata};
+use crypto_app::core::io::RCTMPrng::RCTMPrng;
+use tempfile::TempDir;
+use std::fs;
+
+fn legacy() -> DecryptOptions {
+    DecryptOptions { allow_legacy: true, ..Default::default() }
+}
+
+/// Собирает файл v0 так, как его писала исходная версия: salt || iv || iv || шифртекст,
+/// ключ без соли, 12 байт nonce и 32-битный счётчик, тега нет
+fn write_v0_file(plain: &[u8], password: &str) -> Vec<u8> {
+    let mut iv = [0u8; 16];
+    RCTMPrng::from_entropy().unwrap().fill_bytes(&mut iv[..12]);
+    let metadata = Metadata::legacy(Metadata::new().salt, iv);
+    let key = derive_key(password.as_bytes());
+
+    let mut body = plain.to_vec();
+    Cipher::new(key).apply_keystream_with(CounterLayout::Legacy32, &iv, 0, &mut body).unwrap();
+    let mut output = metadata.to_bytes();
+    output.extend_from_slice(&iv);
+    output.extend(body);
+    output
+}
+
+#[test]
+fn unsalted_files_still_decrypt() {
+    let dir = TempDir::new().unwrap();
+    let encrypted = dir.path().join("legacy.enc");
+    let decrypted = dir.path().join("legacy.txt");
+    let plain = b"written before the salt was used";
+
+    fs::write(&encrypted, write_v0_file(plain, "password")).unwrap();
+
+    decrypt_file_with(&encrypted, &decrypted, "password", &legacy()).expect("Legacy decryption failed");
+    assert_eq!(fs::read(&decrypted).unwrap(), plain);
+}
+
+#[test]
+fn legacy_files_require_opt_in() {
+    let dir = TempDir::new().unwrap();
+    let encrypted = dir.path().join("legacy.enc");
+    let decrypted = dir.path().join("legacy.txt");
+    fs::write(&encrypted, write_v0_file(b"no tag to check", "password")).unwrap();
+
+    let err = decrypt_file(&encrypted, &decrypted, "password").unwrap_err();
+    assert!(err.contains("legacy"), "unexpected error: {}", err);
+    assert!(!decrypted.exists());
+}
+
+#[test]
+fn v0_files_support_random_access() {
+    use crypto_app::core::io::file::DecryptReader;
+    use std::io::{Read, Seek, SeekFrom};
+
+    let dir = TempDir::new().unwrap();
+    let encrypted = dir.path().join("v0.enc");
+    let plain: Vec<u8> = (0..200u8).collect();
+    fs::write(&encrypted, write_v0_file(&plain, "password")).unwrap();
+
+    // Смещение учитывает 48 байт заголовка и повтор IV
+    let mut reader = DecryptReader::open_with(&encrypted, "password", &legacy()).unwrap();
+    assert_eq!(reader.len(), plain.len() as u64);
+    let mut buf = [0u8; 20];
+    reader.seek(SeekFrom::Start(37)).unwrap();
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, &plain[37..57]);
+}
+
+#[test]
+fn baseline_release_file_decrypts() {
+    // Зашифрован исходной версией программы:
+    // `crypto-app encrypt-file -p "baseline password" -i plain.txt -o baseline_v0.enc`
+    let baseline = include_bytes!("fixtures/baseline_v0.enc");
+    let plain = b"Encrypted by the baseline release, before headers and authentication existed.\n";
+    assert_eq!(baseline.len(), 48 + 16 + plain.len());
+
+    let metadata = Metadata::from_bytes(baseline).unwrap();
+    assert!(metadata.is_legacy());
+
+    let dir = TempDir::new().unwrap();
+    let encrypted = dir.path().join("baseline_v0.enc");
+    let decrypted = dir.path().join("plain.txt");
+    fs::write(&encrypted, baseline).unwrap();
+
+    assert!(decrypt_file(&encrypted, &decrypted, "baseline password").is_err());
+    decrypt_file_with(&encrypted, &decrypted, "baseline password", &legacy()).unwrap();
+    assert_eq!(fs::read(&decrypted).unwrap(), plain);
+}
@@ -0,0 +1,58 @@
+use crypto_app::core::crypto::cipher::{Cipher, SpnParams, CONSTANT_TIME_DEFAULT};
+use crypto_app::core::crypto::s_box::{generate_s_box, substitute, substitute_ct, INV_S_BOX, S_BOX};
+
+const KEY: [u8; 32] = *b"0123456789abcdef0123456789ABCDEF";
+
+fn blocks() -> impl Iterator<Item = [u8; 16]> {
+    (0..64u8).map(|seed| std::array::from_fn(|i| seed.wrapping_mul(37).wrapping_add(i as u8 * 11)))
+}
+
+#[test]
+fn masked_substitution_matches_table_lookup() {
+    for table in [S_BOX, INV_S_BOX, generate_s_box(&KEY)] {
+        // Все 256 значений: 16 блоков по 16 байт
+        for chunk in 0..16u8 {
+            let mut fast: [u8; 16] = std::array::from_fn(|i| chunk * 16 + i as u8);
+            let mut masked = fast;
+            substitute(&table, &mut fast);
+            substitute_ct(&table, &mut masked);
+            assert_eq!(fast, masked);
+        }
+    }
+}
+
+#[test]
+fn constant_time_cipher_matches_table_cipher() {
+    let presets = [
+        SpnParams::classic(),
+        SpnParams::hardened(10),
+        SpnParams::hardened(16).with_keyed_s_box(&KEY),
+    ];
+    for params in presets {
+        let table = Cipher::with_params(KEY, &params.clone().with_constant_time(false)).unwrap();
+        let masked = Cipher::with_params(KEY, &params.with_constant_time(true)).unwrap();
+        assert!(!table.is_constant_time());
+        assert!(masked.is_constant_time());
+
+        for block in blocks() {
+            let mut a = block;
+            let mut b = block;
+            table.encrypt_block(&mut a);
+            masked.encrypt_block(&mut b);
+            assert_eq!(a, b);
+
+            masked.decrypt_block(&mut b);
+            assert_eq!(b, block);
+        }
+
+        let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+        let iv = [7u8; 16];
+        assert_eq!(table.apply_ctr(&data, &iv).unwrap(), masked.apply_ctr(&data, &iv).unwrap());
+    }
+}
+
+#[test]
+fn feature_sets_the_default() {
+    assert_eq!(SpnParams::classic().constant_time, CONSTANT_TIME_DEFAULT);
+    assert_eq!(Cipher::new(KEY).is_constant_time(), cfg!(feature = "constant-time"));
+}
@@ -0,0 +1,171 @@
+use crypto_app::core::crypto::cipher::{Cipher, CounterLayout, CtrStream};
+use crypto_app::core::io::meta::{Metadata, MetaError, FLAG_COUNTER64};
+
+/// IV с заданной nonce-частью и значением счётчика в младших байтах
+fn iv_with(nonce: u8, counter: u64) -> [u8; 16] {
+    let mut iv = [nonce; 16];
+    iv[8..].copy_from_slice(&counter.to_be_bytes());
+    iv
+}
+
+#[test]
+fn wide_counter_passes_64_gib() {
+    let cipher = Cipher::new([1u8; 32]);
+    let iv = iv_with(0x5a, 0);
+
+    // Блок 2^32 больше не совпадает с блоком 0
+    let mut first = [0u8; 16];
+    let mut far = [0u8; 16];
+    cipher.apply_ctr_at(&iv, 0, &mut first).unwrap();
+    cipher.apply_ctr_at(&iv, 1 << 32, &mut far).unwrap();
+    assert_ne!(first, far);
+
+    let mut expected = [0u8; 16];
+    cipher.apply_ctr_at(&iv_with(0x5a, 1 << 32), 0, &mut expected).unwrap();
+    assert_eq!(far, expected);
+}
+
+#[test]
+fn counters_wrap_without_touching_the_nonce() {
+    let cipher = Cipher::new([2u8; 32]);
+
+    // 64-битный счётчик: за u64::MAX идёт 0, nonce прежний
+    let mut across = [0u8; 32];
+    cipher.apply_ctr_at(&iv_with(0x10, u64::MAX), 0, &mut across).unwrap();
+    let mut expected = [0u8; 32];
+    cipher.apply_ctr_at(&iv_with(0x10, u64::MAX), 0, &mut expected[..16]).unwrap();
+    cipher.apply_ctr_at(&iv_with(0x10, 0), 0, &mut expected[16..]).unwrap();
+    assert_eq!(across, expected);
+
+    // 32-битный счётчик v0 заворачивается так же, как в исходной версии
+    let mut iv = [0x20u8; 16];
+    iv[12..].copy_from_slice(&u32::MAX.to_be_bytes());
+    let mut next_iv = iv;
+    next_iv[12..].fill(0);
+    let mut across = [0u8; 32];
+    cipher.apply_keystream_with(CounterLayout::Legacy32, &iv, 0, &mut across).unwrap();
+    let mut expected = [0u8; 32];
+    cipher.apply_keystream_with(CounterLayout::Legacy32, &iv, 0, &mut expected[..16]).unwrap();
+    cipher.apply_keystream_with(CounterLayout::Legacy32, &next_iv, 0, &mut expected[16..]).unwrap();
+    assert_eq!(across, expected);
+}
+
+#[test]
+fn block_limit_is_an_error() {
+    let cipher = Cipher::new([3u8; 32]);
+    let iv = [0u8; 16];
+    let mut buf = [0u8; 32];
+
+    assert_eq!(CounterLayout::Legacy32.max_blocks(), 1 << 32);
+    assert!(cipher.apply_ctr_at(&iv, u64::MAX - 1, &mut buf).is_err());
+    assert!(cipher.apply_ctr_at(&iv, u64::MAX - 2, &mut buf).is_ok());
+
+    // v0: гамма дальше 2^32 блоков повторилась бы
+    let end = CounterLayout::Legacy32.max_blocks() * 16;
+    assert!(cipher.apply_keystream_with(CounterLayout::Legacy32, &iv, end - 16, &mut buf[..16]).is_ok());
+    assert!(cipher.apply_keystream_with(CounterLayout::Legacy32, &iv, end - 16, &mut buf).is_err());
+    assert!(cipher.apply_keystream(&iv, end - 16, &mut buf).is_ok());
+
+    let mut ctr = CtrStream::with_layout(&cipher, iv, CounterLayout::Legacy32);
+    assert!(ctr.apply(&mut buf).is_ok());
+}
+
+#[test]
+fn header_records_the_counter_layout() {
+    let metadata = Metadata::new();
+    assert_eq!(metadata.flags & FLAG_COUNTER64, FLAG_COUNTER64);
+    assert_eq!(metadata.counter_layout(), CounterLayout::Wide64);
+    assert_eq!(&metadata.iv[8..], &[0u8; 8]);
+    assert_eq!(Metadata::legacy([0; 32], [0; 16]).counter_layout(), CounterLayout::Legacy32);
+
+    // Файл v1 без флага записан с раскладкой, которой больше нет
+    let mut bytes = metadata.to_bytes();
+    bytes[14] &= !FLAG_COUNTER64;
+    assert_eq!(Metadata::from_bytes(&bytes).unwrap_err(), MetaError::MissingFlags(FLAG_COUNTER64));
+}
+
+#[test]
+fn stream_accepts_unaligned_buffers() {
+    let cipher = Cipher::new([7u8; 32]);
+    let iv = [0x66u8; 16];
+    let data: Vec<u8> = (0..1000u32).map(|i| (i * 3) as u8).collect();
+
+    let mut buf = data.clone();
+    let mut ctr = CtrStream::new(&cipher, iv);
+    for piece in buf.chunks_mut(37) {
+        ctr.apply(piece).unwrap();
+    }
+    assert_eq!(buf, cipher.apply_ctr(&data, &iv).unwrap());
+}
+
+#[test]
+fn iv_counter_overflow_is_an_error() {
+    let mut metadata = Metadata::new();
+    metadata.iv[8..].copy_from_slice(&(u32::MAX as u64).to_be_bytes());
+    assert!(metadata.increment_counter().is_ok());
+    assert_eq!(&metadata.iv[8..], &(1u64 << 32).to_be_bytes());
+
+    metadata.iv[8..].copy_from_slice(&(u64::MAX - 1).to_be_bytes());
+    assert!(metadata.increment_counter().is_ok());
+    assert_eq!(&metadata.iv[8..], &u64::MAX.to_be_bytes());
+    assert!(metadata.increment_counter().is_err());
+    assert_eq!(&metadata.iv[8..], &u64::MAX.to_be_bytes());
+
+    let mut legacy = Metadata::legacy([0; 32], [0xff; 16]);
+    assert!(legacy.increment_counter().is_err());
+    legacy.iv[12..].copy_from_slice(&(u32::MAX - 1).to_be_bytes());
+    assert!(legacy.increment_counter().is_ok());
+    assert_eq!(&legacy.iv[8..12], &[0xff; 4]);
+}
+
+#[test]
+fn keystream_at_any_offset_matches_the_full_stream() {
+    let cipher = Cipher::new([4u8; 32]);
+    let nonce = [0x33u8; 16];
+    let data: Vec<u8> = (0..300u32).map(|i| (i * 13) as u8).collect();
+
+    let mut full = data.clone();
+    cipher.apply_keystream(&nonce, 0, &mut full).unwrap();
+    assert_eq!(full, cipher.apply_ctr(&data, &nonce).unwrap());
+
+    for offset in [0usize, 1, 7, 15, 16, 17, 100, 255, 299] {
+        for len in [0usize, 1, 5, 16, 33] {
+            let end = (offset + len).min(data.len());
+            let mut part = data[offset..end].to_vec();
+            cipher.apply_keystream(&nonce, offset as u64, &mut part).unwrap();
+            assert_eq!(part, &full[offset..end], "offset {}, len {}", offset, len);
+        }
+    }
+}
+
+#[test]
+fn keystream_is_its_own_inverse_in_pieces() {
+    let cipher = Cipher::new([5u8; 32]);
+    let nonce = [0x44u8; 16];
+    let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+
+    let mut buf = data.clone();
+    let mut offset = 0;
+    for piece in buf.chunks_mut(37) {
+        cipher.apply_keystream(&nonce, offset, piece).unwrap();
+        offset += piece.len() as u64;
+    }
+    assert_eq!(buf, cipher.apply_ctr(&data, &nonce).unwrap());
+
+    cipher.apply_keystream(&nonce, 0, &mut buf).unwrap();
+    assert_eq!(buf, data);
+}
+
+#[test]
+fn encrypt_prefixes_the_iv() {
+    let cipher = Cipher::new([6u8; 32]);
+    let iv = [0x55u8; 16];
+    let data = b"in-place keystream".to_vec();
+
+    let encrypted = cipher.encrypt(&data, &iv).unwrap();
+    assert_eq!(&encrypted[..16], &iv);
+    let mut body = data.clone();
+    cipher.apply_keystream(&iv, 0, &mut body).unwrap();
+    assert_eq!(&encrypted[16..], &body[..]);
+    assert_eq!(cipher.decrypt(&encrypted, &iv).unwrap(), data);
+}
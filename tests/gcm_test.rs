@@ -0,0 +1,95 @@
+use crypto_app::core::crypto::cipher::Cipher;
+use crypto_app::core::crypto::gcm::{encrypt_aead, decrypt_aead, gf_mul, ghash, TAG_LEN};
+use crypto_app::core::io::meta::Metadata;
+use hex_literal::hex;
+
+const KEY: [u8; 32] = *b"0123456789abcdef0123456789ABCDEF";
+const NONCE: [u8; 12] = hex!("cafebabefacedbaddecaf888");
+
+/// Значения GHASH из тест-кейса 2 спецификации GCM (McGrew–Viega): от шифра они не зависят
+#[test]
+fn ghash_matches_reference_values() {
+    let h = hex!("66e94bd4ef8a2c3b884cfa59ca342b2e");
+    let c = hex!("0388dace60b6a392f328c2b971b2fe78");
+
+    assert_eq!(
+        gf_mul(u128::from_be_bytes(c), u128::from_be_bytes(h)).to_be_bytes(),
+        hex!("5e2ec746917062882c85b0685353deb7")
+    );
+    assert_eq!(ghash(&h, &[], &c), hex!("f38cbb1ad69223dcc3457ae5b6b0f885"));
+    assert_eq!(ghash(&h, &[], &[]), [0u8; 16]);
+}
+
+#[test]
+fn gf_mul_identity_and_commutativity() {
+    let one = 1u128 << 127;
+    let a = u128::from_be_bytes(hex!("66e94bd4ef8a2c3b884cfa59ca342b2e"));
+    let b = u128::from_be_bytes(hex!("feffe9928665731c6d6a8f9467308308"));
+    assert_eq!(gf_mul(a, one), a);
+    assert_eq!(gf_mul(a, b), gf_mul(b, a));
+    assert_eq!(gf_mul(a, 0), 0);
+}
+
+#[test]
+fn aead_follows_the_gcm_construction() {
+    let cipher = Cipher::new(KEY);
+    let aad = b"header";
+    let plaintext = b"Two roads diverged in a yellow wood";
+
+    let sealed = encrypt_aead(&KEY, &NONCE, aad, plaintext).unwrap();
+    assert_eq!(sealed.len(), plaintext.len() + TAG_LEN);
+    let (ciphertext, tag) = sealed.split_at(plaintext.len());
+
+    let mut counter = [0u8; 16];
+    counter[..12].copy_from_slice(&NONCE);
+    counter[15] = 2;
+    assert_eq!(ciphertext, cipher.apply_ctr(plaintext, &counter).unwrap());
+
+    let mut h = [0u8; 16];
+    cipher.encrypt_block(&mut h);
+    let mut j0 = counter;
+    j0[15] = 1;
+    cipher.encrypt_block(&mut j0);
+    let expected: Vec<u8> = ghash(&h, aad, ciphertext).iter().zip(j0.iter()).map(|(g, m)| g ^ m).collect();
+    assert_eq!(tag, &expected[..]);
+}
+
+#[test]
+fn aead_roundtrip_at_various_lengths() {
+    for len in [0, 1, 15, 16, 17, 100] {
+        let plaintext: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+        let sealed = encrypt_aead(&KEY, &NONCE, b"aad", &plaintext).unwrap();
+        assert_eq!(decrypt_aead(&KEY, &NONCE, b"aad", &sealed).unwrap(), plaintext, "length {}", len);
+    }
+}
+
+#[test]
+fn aead_rejects_any_modification() {
+    let sealed = encrypt_aead(&KEY, &NONCE, b"aad", b"attack at dawn").unwrap();
+
+    for i in 0..sealed.len() {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 1;
+        assert!(decrypt_aead(&KEY, &NONCE, b"aad", &tampered).is_err(), "byte {}", i);
+    }
+
+    assert!(decrypt_aead(&KEY, &NONCE, b"aaD", &sealed).is_err());
+    let mut other_nonce = NONCE;
+    other_nonce[0] ^= 1;
+    assert!(decrypt_aead(&KEY, &other_nonce, b"aad", &sealed).is_err());
+    let mut other_key = KEY;
+    other_key[31] ^= 1;
+    assert!(decrypt_aead(&other_key, &NONCE, b"aad", &sealed).is_err());
+
+    assert!(decrypt_aead(&KEY, &NONCE, b"aad", &sealed[..sealed.len() - 1]).is_err());
+    assert!(decrypt_aead(&KEY, &NONCE, b"aad", &sealed[..TAG_LEN - 1]).is_err());
+}
+
+#[test]
+fn nonce_comes_from_metadata_iv() {
+    let metadata = Metadata::new();
+    assert_eq!(metadata.nonce(), metadata.iv[..12]);
+
+    let sealed = encrypt_aead(&KEY, &metadata.nonce(), &metadata.to_bytes(), b"payload").unwrap();
+    assert_eq!(decrypt_aead(&KEY, &metadata.nonce(), &metadata.to_bytes(), &sealed).unwrap(), b"payload");
+}
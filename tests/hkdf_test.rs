@@ -0,0 +1,94 @@
+use crypto_app::core::crypto::{hkdf::{hkdf, Hkdf}, sha256::Sha256};
+use crypto_app::core::io::file::{decrypt_file, encrypt_file};
+use crypto_app::core::io::meta::{Metadata, MetaError, FLAG_HEADER_TAG};
+use hex_literal::hex;
+use tempfile::TempDir;
+use std::fs;
+
+// RFC 5869, приложение A
+#[test]
+fn rfc5869_case_1() {
+    let hkdf = Hkdf::<Sha256>::extract(&hex!("000102030405060708090a0b0c"), &[0x0b; 22]);
+    assert_eq!(hkdf.prk(), hex!("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"));
+
+    let mut okm = [0u8; 42];
+    hkdf.expand(&hex!("f0f1f2f3f4f5f6f7f8f9"), &mut okm).unwrap();
+    assert_eq!(okm, hex!("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"));
+}
+
+#[test]
+fn rfc5869_case_2() {
+    let ikm: Vec<u8> = (0x00..=0x4f).collect();
+    let salt: Vec<u8> = (0x60..=0xaf).collect();
+    let info: Vec<u8> = (0xb0..=0xff).collect();
+
+    let hkdf = Hkdf::<Sha256>::extract(&salt, &ikm);
+    assert_eq!(hkdf.prk(), hex!("06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244"));
+
+    let mut okm = [0u8; 82];
+    hkdf.expand(&info, &mut okm).unwrap();
+    assert_eq!(okm, hex!(
+        "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c"
+        "59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71"
+        "cc30c58179ec3e87c14c01d5c1f3434f1d87"
+    ));
+}
+
+#[test]
+fn rfc5869_case_3() {
+    let mut okm = [0u8; 42];
+    hkdf::<Sha256>(&[], &[0x0b; 22], &[], &mut okm).unwrap();
+    assert_eq!(okm, hex!("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"));
+
+    let prk = hex!("19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04");
+    let from_prk = Hkdf::<Sha256>::from_prk(&prk).unwrap();
+    assert_eq!(*from_prk.expand_array::<42>(&[]).unwrap(), okm);
+}
+
+#[test]
+fn output_length_is_limited() {
+    let hkdf = Hkdf::<Sha256>::extract(b"salt", b"ikm");
+    let mut max = vec![0u8; 255 * 32];
+    assert!(hkdf.expand(b"", &mut max).is_ok());
+    let mut too_long = vec![0u8; 255 * 32 + 1];
+    assert!(hkdf.expand(b"", &mut too_long).is_err());
+    assert!(Hkdf::<Sha256>::from_prk(&[0u8; 31]).is_err());
+}
+
+#[test]
+fn new_files_carry_header_tag() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.txt");
+    let encrypted = dir.path().join("plain.enc");
+    let decrypted = dir.path().join("plain.dec");
+    fs::write(&plain, b"subkeys").unwrap();
+
+    encrypt_file(&plain, &encrypted, "password").unwrap();
+    let data = fs::read(&encrypted).unwrap();
+    let metadata = Metadata::from_bytes(&data).unwrap();
+    assert!(metadata.flags & FLAG_HEADER_TAG != 0);
+    assert!(metadata.header_tag.is_some());
+
+    // Без тега заголовок v1 не читается
+    let mut untagged = data.clone();
+    untagged[14] &= !FLAG_HEADER_TAG;
+    assert_eq!(Metadata::from_bytes(&untagged).unwrap_err(), MetaError::MissingFlags(FLAG_HEADER_TAG));
+
+    let err = decrypt_file(&encrypted, &decrypted, "wrong").unwrap_err();
+    assert!(err.contains("header"), "unexpected error: {}", err);
+
+    // Подмена IV или соли обнаруживается по тегу заголовка, до проверки всего файла
+    let header_len = metadata.header_len();
+    for pos in [header_len - 1, header_len - 16 - 1] {
+        let mut tampered = data.clone();
+        tampered[pos] ^= 0x01;
+        fs::write(&encrypted, &tampered).unwrap();
+        let err = decrypt_file(&encrypted, &decrypted, "password").unwrap_err();
+        assert!(err.contains("Authentication failed"), "unexpected error: {}", err);
+        assert!(!decrypted.exists());
+    }
+
+    fs::write(&encrypted, &data).unwrap();
+    decrypt_file(&encrypted, &decrypted, "password").unwrap();
+    assert_eq!(fs::read(&decrypted).unwrap(), b"subkeys");
+}
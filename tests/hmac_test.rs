@@ -0,0 +1,95 @@
+use std::io::Write;
+use crypto_app::cli::{from_hex, to_hex};
+use crypto_app::core::crypto::hmac::{Hmac, HmacSha256};
+use crypto_app::core::crypto::sha256::Sha256;
+use crypto_app::core::io::file::{mac_file, verify_file_mac};
+use hex_literal::hex;
+use tempfile::TempDir;
+
+/// RFC 4231, раздел 4: ключ, данные, HMAC-SHA-256
+fn rfc4231() -> Vec<(Vec<u8>, Vec<u8>, [u8; 32])> {
+    vec![
+        (vec![0x0b; 20], b"Hi There".to_vec(),
+            hex!("b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7")),
+        (b"Jefe".to_vec(), b"what do ya want for nothing?".to_vec(),
+            hex!("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")),
+        (vec![0xaa; 20], vec![0xdd; 50],
+            hex!("773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe")),
+        ((1..=25).collect(), vec![0xcd; 50],
+            hex!("82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b")),
+        // Тест 6: ключ длиннее блока хешируется
+        (vec![0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+            hex!("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54")),
+        (vec![0xaa; 131], b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.".to_vec(),
+            hex!("9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2")),
+    ]
+}
+
+#[test]
+fn rfc4231_vectors() {
+    for (key, data, expected) in rfc4231() {
+        assert_eq!(Hmac::<Sha256>::mac(&key, &data), expected);
+
+        // Инкрементально, по одному байту
+        let mut mac = HmacSha256::new(&key);
+        data.iter().for_each(|b| { mac.update(&[*b]); });
+        assert!(mac.clone().verify(&expected));
+        assert_eq!(mac.finalize(), expected);
+    }
+}
+
+#[test]
+fn rfc4231_truncated_case_5() {
+    let mac = Hmac::<Sha256>::mac(&[0x0c; 20], b"Test With Truncation");
+    assert_eq!(mac[..16], hex!("a3b6167473100ee06e0c796c2955552b"));
+
+    let mut verifier = HmacSha256::new(&[0x0c; 20]);
+    verifier.update(b"Test With Truncation");
+    assert!(verifier.verify(&hex!("a3b6167473100ee06e0c796c2955552b")));
+}
+
+#[test]
+fn verify_rejects_wrong_and_too_short_tags() {
+    let key = b"shared secret";
+    let tag = Hmac::<Sha256>::mac(key, b"artifact");
+
+    let check = |t: &[u8]| {
+        let mut mac = HmacSha256::new(key);
+        mac.update(b"artifact");
+        mac.verify(t)
+    };
+    assert!(check(&tag));
+    let mut bad = tag;
+    bad[31] ^= 1;
+    assert!(!check(&bad));
+    assert!(!check(&tag[..15]));
+    assert!(!check(&[]));
+    assert!(!check(&[tag.as_slice(), &[0]].concat()));
+}
+
+#[test]
+fn write_and_file_helpers_match_one_shot() {
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    let expected = Hmac::<Sha256>::mac(b"key", &data);
+
+    let mut mac = HmacSha256::new(b"key");
+    mac.write_all(&data).unwrap();
+    assert_eq!(mac.finalize(), expected);
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("artifact.bin");
+    std::fs::write(&path, &data).unwrap();
+    assert_eq!(mac_file(&path, b"key").unwrap(), expected);
+    assert!(verify_file_mac(&path, b"key", &expected).unwrap());
+    assert!(!verify_file_mac(&path, b"other", &expected).unwrap());
+    assert!(mac_file(&dir.path().join("missing"), b"key").is_err());
+}
+
+#[test]
+fn hex_roundtrip() {
+    let bytes = hex!("00ff10a5");
+    assert_eq!(to_hex(&bytes), "00ff10a5");
+    assert_eq!(from_hex("00FF10a5\n").unwrap(), bytes);
+    assert!(from_hex("abc").is_err());
+    assert!(from_hex("zz").is_err());
+}
@@ -1,5 +1,5 @@
 // use only $cargo test 
-use crypto_app::core::crypto::keygen::derive_key;
+use crypto_app::core::crypto::keygen::{derive_key, derive_key_salted};
 use nistrs::prelude::*;
 use crypto_app::core::io::RCTMPrng::RCTMPrng;
 
@@ -14,7 +14,7 @@ fn test_keygen_nist_full() {
     // Генерация тестовых данных
     for _ in 0..SAMPLE_SIZE / 32 {
         let mut password = [0u8; 32];
-        let mut salt = [0u8; 32];
+        let _salt = [0u8; 32];
         rng.fill_bytes(&mut password);
         
         let key = derive_key(&password);
@@ -73,4 +73,33 @@ fn test_keygen_nist_full() {
     }
 
     assert!(passed >= 5, "Only {} tests passed", passed);
-}
\ No newline at end of file
+}
+#[test]
+fn test_keygen_salted_nist() {
+    // Фиксированные mu и x0: результат не зависит от энтропии системы
+    let mut rng = RCTMPrng::new(3.7, 0.42).expect("Valid PRNG parameters");
+    let password = b"one password for every salt";
+    let mut key_bits = Vec::new();
+
+    // Ключи одного пароля с разными солями
+    for _ in 0..SAMPLE_SIZE / 32 {
+        let mut salt = [0u8; 32];
+        rng.fill_bytes(&mut salt);
+        key_bits.extend(derive_key_salted(password, &salt));
+    }
+
+    let data = BitsData::from_binary(key_bits);
+
+    let (_, p) = frequency_test(&data);
+    assert!(p >= NIST_THRESHOLD, "Frequency test failed: p = {:.4}", p);
+
+    let (_, p) = block_frequency_test(&data, 128).expect("Block Frequency test error");
+    assert!(p >= NIST_THRESHOLD, "Block Frequency test failed: p = {:.4}", p);
+
+    for (i, (_, p)) in cumulative_sums_test(&data).into_iter().enumerate() {
+        assert!(p >= NIST_THRESHOLD, "Cusum test {} failed: p = {:.4}", i, p);
+    }
+
+    let (_, p) = runs_test(&data);
+    assert!(p >= NIST_THRESHOLD, "Runs test failed: p = {:.4}", p);
+}
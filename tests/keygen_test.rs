@@ -1,5 +1,4 @@
-use crypto_app::core::crypto::keygen::derive_key;
-use hex_literal::hex;
+use crypto_app::core::crypto::keygen::{derive_key, derive_key_salted};
 
 
 
@@ -10,3 +9,14 @@ fn key_derivation_consistency() {
     assert_eq!(key1, key2);
 }
 
+
+#[test]
+fn salted_key_depends_on_salt() {
+    let salt_a = [0x11u8; 32];
+    let salt_b = [0x22u8; 32];
+
+    let key_a = derive_key_salted(b"secret", &salt_a);
+    assert_eq!(key_a, derive_key_salted(b"secret", &salt_a));
+    assert_ne!(key_a, derive_key_salted(b"secret", &salt_b));
+    assert_ne!(key_a, derive_key(b"secret"));
+}
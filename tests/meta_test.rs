@@ -0,0 +1,138 @@
+use crypto_app::core::io::file::{encrypt_file, decrypt_file};
+use crypto_app::core::io::meta::{Metadata, MetaError, CipherParams, FLAG_CIPHER_PARAMS, MAGIC, CURRENT_VERSION, LEGACY_LEN};
+use tempfile::TempDir;
+use std::fs;
+
+#[test]
+fn header_roundtrip() {
+    let metadata = Metadata::new();
+    let bytes = metadata.to_bytes();
+
+    assert!(bytes.starts_with(&MAGIC));
+    assert_eq!(bytes[8], CURRENT_VERSION);
+    assert_eq!(bytes.len(), metadata.header_len());
+    assert_eq!(u16::from_be_bytes([bytes[9], bytes[10]]) as usize, bytes.len());
+
+    let parsed = Metadata::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed, metadata);
+}
+
+#[test]
+fn cipher_params_section_roundtrip() {
+    let classic = Metadata::new();
+    let mut hardened = Metadata::new();
+    hardened.set_cipher_params(CipherParams::hardened(16));
+
+    // Классическим параметрам секция не нужна: заголовок тот же, что раньше
+    assert_eq!(classic.flags & FLAG_CIPHER_PARAMS, 0);
+    assert_eq!(hardened.flags & FLAG_CIPHER_PARAMS, FLAG_CIPHER_PARAMS);
+    assert_eq!(hardened.header_len(), classic.header_len() + 5);
+
+    let parsed = Metadata::from_bytes(&hardened.to_bytes()).unwrap();
+    assert_eq!(parsed, hardened);
+    assert_eq!(parsed.cipher_params, CipherParams::hardened(16));
+
+    hardened.set_cipher_params(CipherParams::CLASSIC);
+    assert_eq!(hardened.flags & FLAG_CIPHER_PARAMS, 0);
+    assert_eq!(hardened.header_len(), classic.header_len());
+}
+
+#[test]
+fn bad_cipher_params_are_rejected() {
+    let mut metadata = Metadata::new();
+    metadata.set_cipher_params(CipherParams::hardened(10));
+    let bytes = metadata.to_bytes();
+    // Секция сразу за фиксированной частью: len, rounds, s-box, p-box, key schedule
+    assert_eq!(bytes[16..21], [4, 10, 0, 1, 1]);
+
+    let mut zero_rounds = bytes.clone();
+    zero_rounds[17] = 0;
+    assert_eq!(Metadata::from_bytes(&zero_rounds), Err(MetaError::BadCipherParams));
+
+    let mut unknown_s_box = bytes.clone();
+    unknown_s_box[18] = 9;
+    assert_eq!(Metadata::from_bytes(&unknown_s_box), Err(MetaError::UnknownSBox(9)));
+
+    let mut unknown_p_box = bytes.clone();
+    unknown_p_box[19] = 9;
+    assert_eq!(Metadata::from_bytes(&unknown_p_box), Err(MetaError::UnknownPBox(9)));
+
+    let mut unknown_schedule = bytes.clone();
+    unknown_schedule[20] = 9;
+    assert_eq!(Metadata::from_bytes(&unknown_schedule), Err(MetaError::UnknownKeySchedule(9)));
+
+    let mut bad_section_len = bytes.clone();
+    bad_section_len[16] = 2;
+    assert!(Metadata::from_bytes(&bad_section_len).is_err());
+}
+
+#[test]
+fn short_cipher_params_section_is_rejected() {
+    let mut metadata = Metadata::new();
+    metadata.set_cipher_params(CipherParams::hardened(10));
+    let mut bytes = metadata.to_bytes();
+
+    // Секция без байта схемы ключей
+    bytes.remove(20);
+    bytes[16] = 3;
+    let header_len = u16::from_be_bytes([bytes[9], bytes[10]]) - 1;
+    bytes[9..11].copy_from_slice(&header_len.to_be_bytes());
+
+    assert_eq!(Metadata::from_bytes(&bytes), Err(MetaError::BadCipherParams));
+}
+
+#[test]
+fn header_without_magic_is_v0() {
+    let bytes = [0xabu8; LEGACY_LEN];
+    let parsed = Metadata::from_bytes(&bytes).unwrap();
+
+    assert!(parsed.is_legacy());
+    assert_eq!(parsed.header_len(), LEGACY_LEN);
+    assert_eq!(parsed.salt, [0xab; 32]);
+    assert_eq!(parsed.to_bytes(), bytes);
+}
+
+#[test]
+fn unknown_version_is_rejected() {
+    let mut bytes = Metadata::new().to_bytes();
+    bytes[8] = 7;
+    assert_eq!(Metadata::from_bytes(&bytes), Err(MetaError::UnsupportedVersion(7)));
+}
+
+#[test]
+fn malformed_headers_are_rejected() {
+    let bytes = Metadata::new().to_bytes();
+
+    let mut unknown_flags = bytes.clone();
+    unknown_flags[14] = 0x80;
+    assert_eq!(Metadata::from_bytes(&unknown_flags), Err(MetaError::UnknownFlags(0x80)));
+
+    let mut unknown_mode = bytes.clone();
+    unknown_mode[12] = 0xee;
+    assert_eq!(Metadata::from_bytes(&unknown_mode), Err(MetaError::UnknownMode(0xee)));
+
+    let mut bad_len = bytes.clone();
+    bad_len[10] ^= 1;
+    assert!(matches!(Metadata::from_bytes(&bad_len), Err(MetaError::BadHeaderLength(_))));
+
+    assert_eq!(Metadata::from_bytes(&bytes[..20]), Err(MetaError::TooShort));
+}
+
+#[test]
+fn encrypted_file_reports_unsupported_version() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.txt");
+    let encrypted = dir.path().join("plain.enc");
+    let decrypted = dir.path().join("plain.dec");
+    fs::write(&plain, b"versioned").unwrap();
+
+    encrypt_file(&plain, &encrypted, "password").unwrap();
+    let mut bytes = fs::read(&encrypted).unwrap();
+    assert!(bytes.starts_with(&MAGIC));
+    bytes[8] = CURRENT_VERSION + 1;
+    fs::write(&encrypted, &bytes).unwrap();
+
+    let err = decrypt_file(&encrypted, &decrypted, "password").unwrap_err();
+    assert!(err.contains("Unsupported format version"), "unexpected error: {}", err);
+    assert!(!decrypted.exists());
+}
@@ -0,0 +1,162 @@
+use crypto_app::core::crypto::cipher::{Cipher, CtrStream};
+use crypto_app::core::crypto::modes::{CipherMode, Cbc, Cfb, Cfb8, Ofb, pkcs7_pad};
+use crypto_app::core::io::file::{encrypt_file_with, decrypt_file, EncryptOptions};
+use crypto_app::core::io::meta::{Metadata, ModeId};
+use hex_literal::hex;
+use tempfile::TempDir;
+use std::fs;
+
+const PLAINTEXT: &[u8] = b"Two roads diverged in a yellow wood";
+
+fn key() -> [u8; 32] {
+    core::array::from_fn(|i| i as u8)
+}
+
+fn iv() -> [u8; 16] {
+    core::array::from_fn(|i| 0xf0 + i as u8)
+}
+
+fn encrypt_with(mut mode: impl CipherMode, data: &[u8]) -> Vec<u8> {
+    let mut buf = data.to_vec();
+    if mode.padded() {
+        pkcs7_pad(&mut buf);
+    }
+    mode.encrypt(&mut buf).unwrap();
+    buf
+}
+
+/// Расшифровывает кусками по 32 байта, чтобы проверить перенос состояния между буферами
+fn decrypt_in_pieces(mut mode: impl CipherMode, data: &[u8]) -> Vec<u8> {
+    let mut buf = data.to_vec();
+    for chunk in buf.chunks_mut(32) {
+        mode.decrypt(chunk).unwrap();
+    }
+    buf
+}
+
+/// Регрессионные векторы: получены этой реализацией и ловят случайные изменения
+/// вывода. Правильность самих режимов проверяет `modes_follow_their_definitions`
+#[test]
+fn regression_vectors() {
+    let cipher = Cipher::new(key());
+
+    assert_eq!(
+        encrypt_with(CtrStream::new(&cipher, iv()), PLAINTEXT),
+        hex!("a81f224febb4c4a331e24a5907c9983f990c6d06f7fbc4e73ba7425c1edbce93930729")
+    );
+    assert_eq!(
+        encrypt_with(Cbc::new(&cipher, iv()), PLAINTEXT),
+        hex!("4ac7ff1190221a5f98dab1a8e3065345b2e3cef4f3ea22400eb206b6992f07399b132039f31e8afe08b22b33e19c2db2")
+    );
+    assert_eq!(
+        encrypt_with(Cfb::new(&cipher, iv()), PLAINTEXT),
+        hex!("a81f224febb4c4a331e24a5907c9983fc8fe39bee2222e40db7710fdc7f49ef5ebabb6")
+    );
+    assert_eq!(
+        encrypt_with(Cfb8::new(&cipher, iv()), PLAINTEXT),
+        hex!("a8833b47856192f14ca0e71a6e4cefa3c865650038d178d49eaa68e91f90400ceb5358")
+    );
+    assert_eq!(
+        encrypt_with(Ofb::new(&cipher, iv()), PLAINTEXT),
+        hex!("a81f224febb4c4a331e24a5907c9983f7dc61a33a35801bc905a6e1493b67a6a45662b")
+    );
+}
+
+/// Каждый режим пересчитывается по определению из SP 800-38A через `encrypt_block`
+#[test]
+fn modes_follow_their_definitions() {
+    let cipher = Cipher::new(key());
+    let e = |block: [u8; 16]| {
+        let mut block = block;
+        cipher.encrypt_block(&mut block);
+        block
+    };
+    let xor = |a: &[u8], b: &[u8]| -> Vec<u8> { a.iter().zip(b).map(|(x, y)| x ^ y).collect() };
+
+    // CBC: C_i = E(P_i ^ C_{i-1}), C_0 = IV, открытый текст дополнен PKCS#7
+    let mut padded = PLAINTEXT.to_vec();
+    pkcs7_pad(&mut padded);
+    let mut expected = Vec::new();
+    let mut previous = iv();
+    for block in padded.chunks(16) {
+        previous = e(xor(block, &previous).try_into().unwrap());
+        expected.extend_from_slice(&previous);
+    }
+    assert_eq!(encrypt_with(Cbc::new(&cipher, iv()), PLAINTEXT), expected);
+
+    // CFB: C_i = P_i ^ E(C_{i-1}), последний неполный блок усечён
+    let mut expected = Vec::new();
+    let mut previous = iv();
+    for block in PLAINTEXT.chunks(16) {
+        let c = xor(block, &e(previous));
+        if c.len() == 16 {
+            previous = c[..].try_into().unwrap();
+        }
+        expected.extend(c);
+    }
+    assert_eq!(encrypt_with(Cfb::new(&cipher, iv()), PLAINTEXT), expected);
+
+    // CFB-8: байт гаммы — старший байт E(регистра), регистр сдвигается на байт шифртекста
+    let mut expected = Vec::new();
+    let mut register = iv();
+    for &p in PLAINTEXT {
+        let c = p ^ e(register)[0];
+        register.copy_within(1.., 0);
+        register[15] = c;
+        expected.push(c);
+    }
+    assert_eq!(encrypt_with(Cfb8::new(&cipher, iv()), PLAINTEXT), expected);
+
+    // OFB: O_i = E(O_{i-1}), C_i = P_i ^ O_i
+    let mut expected = Vec::new();
+    let mut output = iv();
+    for block in PLAINTEXT.chunks(16) {
+        output = e(output);
+        expected.extend(xor(block, &output));
+    }
+    assert_eq!(encrypt_with(Ofb::new(&cipher, iv()), PLAINTEXT), expected);
+}
+
+#[test]
+fn every_mode_roundtrips_and_differs_from_ctr() {
+    let cipher = Cipher::new(key());
+    let data: Vec<u8> = (0..200u8).collect();
+    let ctr = encrypt_with(CtrStream::new(&cipher, iv()), &data);
+
+    let cbc = encrypt_with(Cbc::new(&cipher, iv()), &data);
+    let mut plain = decrypt_in_pieces(Cbc::new(&cipher, iv()), &cbc);
+    plain.truncate(data.len());
+    assert_eq!(plain, data);
+    assert_ne!(cbc[..data.len()], ctr[..]);
+
+    let cfb = encrypt_with(Cfb::new(&cipher, iv()), &data);
+    assert_eq!(decrypt_in_pieces(Cfb::new(&cipher, iv()), &cfb), data);
+    assert_ne!(cfb, ctr);
+
+    let cfb8 = encrypt_with(Cfb8::new(&cipher, iv()), &data);
+    assert_eq!(decrypt_in_pieces(Cfb8::new(&cipher, iv()), &cfb8), data);
+    assert_ne!(cfb8, ctr);
+
+    let ofb = encrypt_with(Ofb::new(&cipher, iv()), &data);
+    assert_eq!(decrypt_in_pieces(Ofb::new(&cipher, iv()), &ofb), data);
+    assert_ne!(ofb, ctr);
+}
+
+#[test]
+fn file_roundtrip_in_every_mode() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.txt");
+    let data: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+    fs::write(&plain, &data).unwrap();
+
+    for mode in [ModeId::CtrHmac, ModeId::CbcHmac, ModeId::CfbHmac, ModeId::Cfb8Hmac, ModeId::OfbHmac] {
+        let encrypted = dir.path().join(format!("{:?}.enc", mode));
+        let decrypted = dir.path().join(format!("{:?}.dec", mode));
+
+        encrypt_file_with(&plain, &encrypted, "password", &EncryptOptions { mode, ..Default::default() }).unwrap();
+        assert_eq!(Metadata::from_bytes(&fs::read(&encrypted).unwrap()).unwrap().mode, mode);
+
+        decrypt_file(&encrypted, &decrypted, "password").unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), data, "{:?}", mode);
+    }
+}
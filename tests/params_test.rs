@@ -0,0 +1,111 @@
+use crypto_app::core::crypto::cipher::{Cipher, KeySchedule, SpnParams, MAX_ROUNDS};
+use crypto_app::core::crypto::p_box::TRANSPOSE_P_BOX;
+use crypto_app::core::io::file::{encrypt_file_with, decrypt_file, EncryptOptions};
+use crypto_app::core::io::meta::{CipherParams, Metadata};
+use tempfile::TempDir;
+use std::fs;
+
+const KEY: [u8; 32] = *b"0123456789abcdef0123456789ABCDEF";
+
+fn changed_bytes(cipher: &Cipher, bit: usize) -> usize {
+    let mut a = [0x5au8; 16];
+    let mut b = a;
+    b[bit / 8] ^= 1 << (bit % 8);
+    cipher.encrypt_block(&mut a);
+    cipher.encrypt_block(&mut b);
+    a.iter().zip(b.iter()).filter(|(x, y)| x != y).count()
+}
+
+#[test]
+fn classic_params_match_the_original_cipher() {
+    let original = Cipher::new(KEY);
+    let configured = Cipher::with_params(KEY, &SpnParams::classic()).unwrap();
+    assert_eq!(configured.rounds(), 2);
+
+    for seed in 0..16u8 {
+        let mut a = [seed; 16];
+        let mut b = a;
+        original.encrypt_block(&mut a);
+        configured.encrypt_block(&mut b);
+        assert_eq!(a, b);
+    }
+}
+
+#[test]
+fn every_round_count_roundtrips() {
+    for rounds in [1, 2, 3, 10, 16, MAX_ROUNDS] {
+        let cipher = Cipher::with_params(KEY, &SpnParams::hardened(rounds)).unwrap();
+        let original: [u8; 16] = core::array::from_fn(|i| i as u8 * 11);
+        let mut block = original;
+        cipher.encrypt_block(&mut block);
+        assert_ne!(block, original);
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, original, "{} rounds", rounds);
+    }
+}
+
+#[test]
+fn builder_overrides_single_fields() {
+    let params = SpnParams::classic()
+        .with_rounds(6)
+        .with_p_box(TRANSPOSE_P_BOX)
+        .with_key_schedule(KeySchedule::Expanded);
+    assert_eq!(params, SpnParams::hardened(6));
+
+    let mut s_box: [u8; 256] = core::array::from_fn(|i| i as u8);
+    s_box.swap(0, 1);
+    let params = SpnParams::classic().with_s_box(s_box);
+    assert!(params.validate().is_ok());
+    assert_ne!(params, SpnParams::classic());
+}
+
+#[test]
+fn invalid_params_are_rejected() {
+    assert!(Cipher::with_params(KEY, &SpnParams::classic().with_rounds(0)).is_err());
+    assert!(Cipher::with_params(KEY, &SpnParams::classic().with_rounds(MAX_ROUNDS + 1)).is_err());
+
+    let mut s_box = SpnParams::classic().s_box;
+    s_box[0] = s_box[1];
+    assert_eq!(
+        Cipher::with_params(KEY, &SpnParams::classic().with_s_box(s_box)).err(),
+        Some("S-box is not a permutation")
+    );
+
+    let mut p_box = TRANSPOSE_P_BOX;
+    p_box[5] = 200;
+    assert_eq!(
+        Cipher::with_params(KEY, &SpnParams::classic().with_p_box(p_box)).err(),
+        Some("P-box is not a permutation")
+    );
+}
+
+#[test]
+fn transpose_p_box_diffuses_across_the_block() {
+    // Разворот битов переносит байт целиком в другой байт: один бит входа меняет один байт
+    let classic = Cipher::new(KEY);
+    assert!((0..128).all(|bit| changed_bytes(&classic, bit) == 1));
+
+    let hardened = Cipher::with_params(KEY, &SpnParams::hardened(10)).unwrap();
+    assert!((0..128).all(|bit| changed_bytes(&hardened, bit) >= 14));
+}
+
+#[test]
+fn file_records_cipher_params() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.txt");
+    let data: Vec<u8> = (0..5000u32).map(|i| (i % 253) as u8).collect();
+    fs::write(&plain, &data).unwrap();
+
+    for params in [CipherParams::CLASSIC, CipherParams::hardened(10), CipherParams::hardened(16)] {
+        let encrypted = dir.path().join(format!("{}.enc", params.rounds));
+        let decrypted = dir.path().join(format!("{}.dec", params.rounds));
+        let options = EncryptOptions { params, ..Default::default() };
+
+        encrypt_file_with(&plain, &encrypted, "password", &options).unwrap();
+        let header = Metadata::from_bytes(&fs::read(&encrypted).unwrap()).unwrap();
+        assert_eq!(header.cipher_params, params);
+
+        decrypt_file(&encrypted, &decrypted, "password").unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), data);
+    }
+}
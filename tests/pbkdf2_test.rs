@@ -0,0 +1,87 @@
+use crypto_app::cli::{Kdf, KdfArgs};
+use crypto_app::core::crypto::{pbkdf2::{pbkdf2, MAX_ITERATIONS}, sha256::Sha256};
+use crypto_app::core::io::file::{decrypt_file, encrypt_file_with, EncryptOptions};
+use crypto_app::core::io::meta::{KdfId, KdfParams, MetaError, Metadata};
+use hex_literal::hex;
+use tempfile::TempDir;
+use std::fs;
+
+// RFC 7914, раздел 11
+#[test]
+fn rfc7914_vectors() {
+    let mut out = [0u8; 64];
+    pbkdf2::<Sha256>(b"passwd", b"salt", 1, &mut out).unwrap();
+    assert_eq!(out, hex!(
+        "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        "49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+    ));
+
+    pbkdf2::<Sha256>(b"Password", b"NaCl", 80000, &mut out).unwrap();
+    assert_eq!(out, hex!(
+        "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56"
+        "a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d"
+    ));
+}
+
+#[test]
+fn short_output_is_prefix() {
+    let mut long = [0u8; 64];
+    let mut short = [0u8; 20];
+    pbkdf2::<Sha256>(b"passwd", b"salt", 3, &mut long).unwrap();
+    pbkdf2::<Sha256>(b"passwd", b"salt", 3, &mut short).unwrap();
+    assert_eq!(short, long[..20]);
+    assert!(pbkdf2::<Sha256>(b"passwd", b"salt", 0, &mut short).is_err());
+}
+
+#[test]
+fn kdf_params_round_trip_through_header() {
+    let mut metadata = Metadata::new();
+    metadata.set_kdf(KdfParams::Pbkdf2 { iterations: 1234 });
+    let parsed = Metadata::from_bytes(&metadata.to_bytes()).unwrap();
+    assert_eq!(parsed.kdf, KdfId::Pbkdf2);
+    assert_eq!(KdfParams::from_bytes(parsed.kdf, &parsed.kdf_params), Ok(KdfParams::Pbkdf2 { iterations: 1234 }));
+
+    metadata.set_kdf(KdfParams::Pbkdf2 { iterations: MAX_ITERATIONS });
+    assert!(Metadata::from_bytes(&metadata.to_bytes()).is_ok());
+
+    // Ноль итераций, больше MAX_ITERATIONS и параметры не той длины
+    for params in [vec![0, 0, 0, 0], (MAX_ITERATIONS + 1).to_be_bytes().to_vec(), vec![0xff; 4], vec![0, 0, 1]] {
+        metadata.kdf_params = params;
+        assert_eq!(Metadata::from_bytes(&metadata.to_bytes()).unwrap_err(), MetaError::BadKdfParams);
+    }
+    metadata.set_kdf(KdfParams::Billiard);
+    metadata.kdf_params = vec![1];
+    assert_eq!(Metadata::from_bytes(&metadata.to_bytes()).unwrap_err(), MetaError::BadKdfParams);
+}
+
+#[test]
+fn pbkdf2_files_decrypt() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.txt");
+    let encrypted = dir.path().join("plain.enc");
+    let decrypted = dir.path().join("plain.dec");
+    fs::write(&plain, b"standards-based key derivation").unwrap();
+
+    let options = EncryptOptions { kdf: KdfParams::Pbkdf2 { iterations: 1000 }, ..Default::default() };
+    encrypt_file_with(&plain, &encrypted, "password", &options).unwrap();
+
+    let metadata = Metadata::from_bytes(&fs::read(&encrypted).unwrap()).unwrap();
+    assert_eq!(metadata.kdf, KdfId::Pbkdf2);
+    assert_eq!(metadata.kdf_params, 1000u32.to_be_bytes());
+
+    decrypt_file(&encrypted, &decrypted, "password").unwrap();
+    assert_eq!(fs::read(&decrypted).unwrap(), b"standards-based key derivation");
+    fs::remove_file(&decrypted).unwrap();
+    assert!(decrypt_file(&encrypted, &decrypted, "wrong").is_err());
+    assert!(!decrypted.exists());
+}
+
+#[test]
+fn cli_kdf_selection() {
+    let args = |kdf, iterations| KdfArgs { kdf, iterations, ..Default::default() };
+    assert_eq!(args(Kdf::Billiard, 0).params(), Ok(KdfParams::Billiard));
+    assert_eq!(args(Kdf::Pbkdf2, 5000).params(), Ok(KdfParams::Pbkdf2 { iterations: 5000 }));
+    assert!(args(Kdf::Pbkdf2, 0).params().is_err());
+    assert!(args(Kdf::Pbkdf2, MAX_ITERATIONS).params().is_ok());
+    assert!(args(Kdf::Pbkdf2, MAX_ITERATIONS + 1).params().is_err());
+}
@@ -0,0 +1,77 @@
+use crypto_app::core::crypto::permutation::Permutation;
+use crypto_app::core::crypto::p_box::{P_BOX, TRANSPOSE_P_BOX};
+
+/// Простой LCG: тестам нужна воспроизводимость, а не стойкость
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    fn block(&mut self) -> [u8; 16] {
+        std::array::from_fn(|_| self.next() as u8)
+    }
+}
+
+fn random_p_box(rng: &mut Lcg) -> [usize; 128] {
+    let mut p_box: [usize; 128] = std::array::from_fn(|i| i);
+    for i in (1..128).rev() {
+        p_box.swap(i, rng.next() as usize % (i + 1));
+    }
+    p_box
+}
+
+fn p_boxes() -> Vec<[usize; 128]> {
+    let mut rng = Lcg(0x5eed);
+    let identity: [usize; 128] = std::array::from_fn(|i| i);
+    let mut boxes = vec![P_BOX, TRANSPOSE_P_BOX, identity];
+    boxes.extend((0..20).map(|_| random_p_box(&mut rng)));
+    boxes
+}
+
+#[test]
+fn table_permutation_matches_bitwise() {
+    let mut rng = Lcg(1);
+    for p_box in p_boxes() {
+        for permutation in [Permutation::forward(&p_box), Permutation::inverse(&p_box)] {
+            for _ in 0..200 {
+                let mut fast = rng.block();
+                let mut reference = fast;
+                permutation.apply(&mut fast);
+                permutation.apply_bitwise(&mut reference);
+                assert_eq!(fast, reference);
+            }
+        }
+    }
+}
+
+#[test]
+fn inverse_undoes_forward() {
+    let mut rng = Lcg(2);
+    for p_box in p_boxes() {
+        let forward = Permutation::forward(&p_box);
+        let inverse = Permutation::inverse(&p_box);
+        for _ in 0..200 {
+            let block = rng.block();
+            let mut buf = block;
+            forward.apply(&mut buf);
+            inverse.apply(&mut buf);
+            assert_eq!(buf, block);
+        }
+    }
+}
+
+#[test]
+fn permutation_preserves_bit_count() {
+    let mut rng = Lcg(3);
+    let permutation = Permutation::forward(&random_p_box(&mut rng));
+    for _ in 0..200 {
+        let block = rng.block();
+        let mut buf = block;
+        permutation.apply(&mut buf);
+        let ones = |b: &[u8; 16]| b.iter().map(|x| x.count_ones()).sum::<u32>();
+        assert_eq!(ones(&buf), ones(&block));
+    }
+}
@@ -0,0 +1,94 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use crypto_app::cli::ByteRange;
+use crypto_app::core::io::file::{encrypt_file, encrypt_file_with, decrypt_range, DecryptReader, DecryptOptions, EncryptOptions};
+use crypto_app::core::io::meta::{CipherParams, ModeId};
+use tempfile::TempDir;
+
+fn sample(len: usize) -> Vec<u8> {
+    (0..len as u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect()
+}
+
+#[test]
+fn reader_seeks_to_arbitrary_offsets() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.bin");
+    let encrypted = dir.path().join("plain.crypt");
+    let data = sample(100_000);
+    fs::write(&plain, &data).unwrap();
+    encrypt_file(&plain, &encrypted, "password").unwrap();
+
+    let mut reader = DecryptReader::open(&encrypted, "password").unwrap();
+    assert_eq!(reader.len(), data.len() as u64);
+
+    let mut all = Vec::new();
+    reader.read_to_end(&mut all).unwrap();
+    assert_eq!(all, data);
+
+    for (start, len) in [(0usize, 10usize), (1, 16), (15, 17), (4095, 5000), (99_990, 10), (50_000, 0)] {
+        let mut buf = vec![0u8; len];
+        reader.seek(SeekFrom::Start(start as u64)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, &data[start..start + len], "start {}", start);
+    }
+
+    // Относительные перемещения и чтение за концом
+    assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), data.len() as u64 - 3);
+    let mut tail = Vec::new();
+    reader.read_to_end(&mut tail).unwrap();
+    assert_eq!(tail, &data[data.len() - 3..]);
+    assert_eq!(reader.seek(SeekFrom::Current(-10)).unwrap(), data.len() as u64 - 10);
+    reader.seek(SeekFrom::Start(data.len() as u64 + 5)).unwrap();
+    assert_eq!(reader.read(&mut [0u8; 4]).unwrap(), 0);
+    assert!(reader.seek(SeekFrom::Current(-(data.len() as i64) - 6)).is_err());
+}
+
+#[test]
+fn range_is_decrypted_and_clamped() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.bin");
+    let encrypted = dir.path().join("plain.crypt");
+    let out = dir.path().join("range.bin");
+    let data = sample(5000);
+    fs::write(&plain, &data).unwrap();
+    let options = EncryptOptions { params: CipherParams::hardened(10), ..Default::default() };
+    encrypt_file_with(&plain, &encrypted, "password", &options).unwrap();
+
+    decrypt_range(&encrypted, &out, "password", 1234, 100, &DecryptOptions::default()).unwrap();
+    assert_eq!(fs::read(&out).unwrap(), &data[1234..1334]);
+
+    decrypt_range(&encrypted, &out, "password", 4990, 100, &DecryptOptions::default()).unwrap();
+    assert_eq!(fs::read(&out).unwrap(), &data[4990..]);
+
+    assert!(decrypt_range(&encrypted, &out, "password", 5001, 1, &DecryptOptions::default()).is_err());
+}
+
+#[test]
+fn reader_rejects_wrong_password_tampering_and_other_modes() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.bin");
+    let encrypted = dir.path().join("plain.crypt");
+    fs::write(&plain, sample(1000)).unwrap();
+    encrypt_file(&plain, &encrypted, "password").unwrap();
+
+    assert!(DecryptReader::open(&encrypted, "wrong").is_err());
+
+    let mut bytes = fs::read(&encrypted).unwrap();
+    let mid = bytes.len() / 2;
+    bytes[mid] ^= 1;
+    fs::write(&encrypted, &bytes).unwrap();
+    assert!(DecryptReader::open(&encrypted, "password").is_err());
+
+    let options = EncryptOptions { mode: ModeId::CbcHmac, ..Default::default() };
+    encrypt_file_with(&plain, &encrypted, "password", &options).unwrap();
+    let err = DecryptReader::open(&encrypted, "password").err().unwrap();
+    assert!(err.contains("CTR"), "{}", err);
+}
+
+#[test]
+fn cli_range_parses() {
+    assert_eq!("10:20".parse::<ByteRange>().unwrap(), ByteRange { start: 10, len: 20 });
+    assert!("10".parse::<ByteRange>().is_err());
+    assert!("a:1".parse::<ByteRange>().is_err());
+    assert!("1:-1".parse::<ByteRange>().is_err());
+}
@@ -0,0 +1,119 @@
+use crypto_app::cli::{Kdf, KdfArgs};
+use crypto_app::core::crypto::keygen::{derive_key_salted, derive_key_scrypt};
+use crypto_app::core::crypto::scrypt::{scrypt, ScryptParams};
+use crypto_app::core::io::file::{decrypt_file, encrypt_file_with, EncryptOptions};
+use crypto_app::core::io::meta::{KdfId, KdfParams, MetaError, Metadata};
+use hex_literal::hex;
+use tempfile::TempDir;
+use std::fs;
+
+fn params(log_n: u8, r: u32, p: u32) -> ScryptParams {
+    ScryptParams::new(log_n, r, p).unwrap()
+}
+
+// RFC 7914, раздел 12 (последний вектор с N = 2^20 слишком долгий для тестов)
+#[test]
+fn rfc7914_vectors() {
+    let mut out = [0u8; 64];
+    scrypt(b"", b"", &params(4, 1, 1), &mut out).unwrap();
+    assert_eq!(out, hex!(
+        "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442"
+        "fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+    ));
+
+    scrypt(b"password", b"NaCl", &params(10, 8, 16), &mut out).unwrap();
+    assert_eq!(out, hex!(
+        "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162"
+        "2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+    ));
+
+    scrypt(b"pleaseletmein", b"SodiumChloride", &params(14, 8, 1), &mut out).unwrap();
+    assert_eq!(out, hex!(
+        "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2"
+        "d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887"
+    ));
+}
+
+#[test]
+fn invalid_params_are_rejected() {
+    assert!(ScryptParams::new(0, 8, 1).is_err());
+    assert!(ScryptParams::new(14, 0, 1).is_err());
+    assert!(ScryptParams::new(14, 8, 0).is_err());
+    // N < 2^(16 r)
+    assert!(ScryptParams::new(16, 1, 1).is_err());
+    assert!(ScryptParams::new(15, 1, 1).is_ok());
+    // Больше MAX_MEMORY на один ROMix
+    assert!(ScryptParams::new(21, 8, 1).is_err());
+    // Память считается вместе с p рабочими блоками, работа — по всем p ROMix
+    assert!(ScryptParams::new(20, 8, 1).is_err());
+    assert!(ScryptParams::new(21, 2, 1).is_ok());
+    assert!(ScryptParams::new(19, 8, 8).is_ok());
+    assert!(ScryptParams::new(19, 8, 16).is_err());
+    assert!(ScryptParams::new(4, 1, 17).is_err());
+    assert_eq!(ScryptParams::DEFAULT.memory(), 128 << 20);
+}
+
+#[test]
+fn billiard_prehash_changes_key() {
+    let salt = [7u8; 32];
+    let p = params(4, 1, 1);
+    let plain = derive_key_scrypt(b"password", &salt, &p, false).unwrap();
+    let prehashed = derive_key_scrypt(b"password", &salt, &p, true).unwrap();
+    assert_ne!(plain, prehashed);
+
+    let mut expected = [0u8; 32];
+    scrypt(&derive_key_salted(b"password", &salt), &salt, &p, &mut expected).unwrap();
+    assert_eq!(prehashed, expected);
+}
+
+#[test]
+fn scrypt_params_round_trip_through_header() {
+    let mut metadata = Metadata::new();
+    let kdf = KdfParams::Scrypt { params: params(10, 8, 2), billiard_prehash: true };
+    metadata.set_kdf(kdf);
+    assert_eq!(metadata.kdf_params, [10, 0, 0, 0, 8, 0, 0, 0, 2, 1]);
+    let parsed = Metadata::from_bytes(&metadata.to_bytes()).unwrap();
+    assert_eq!(parsed.kdf, KdfId::Scrypt);
+    assert_eq!(KdfParams::from_bytes(parsed.kdf, &parsed.kdf_params), Ok(kdf));
+
+    // Невалидный pre-hash, N, требующий слишком много памяти, огромное p
+    // (r = 1, p = 2^29) и обрезанные параметры
+    for bad in [
+        vec![10, 0, 0, 0, 8, 0, 0, 0, 2, 2],
+        vec![40, 0, 0, 0, 8, 0, 0, 0, 1, 0],
+        vec![4, 1, 0, 0, 0, 0, 0, 0, 0x20, 0],
+        vec![10, 0, 0, 0, 8, 0, 0, 0, 2],
+    ] {
+        metadata.kdf_params = bad;
+        assert_eq!(Metadata::from_bytes(&metadata.to_bytes()).unwrap_err(), MetaError::BadKdfParams);
+    }
+}
+
+#[test]
+fn scrypt_files_decrypt() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.txt");
+    let encrypted = dir.path().join("plain.enc");
+    let decrypted = dir.path().join("plain.dec");
+    fs::write(&plain, b"memory-hard").unwrap();
+
+    for billiard_prehash in [false, true] {
+        let kdf = KdfParams::Scrypt { params: params(8, 8, 1), billiard_prehash };
+        let options = EncryptOptions { kdf, ..Default::default() };
+        encrypt_file_with(&plain, &encrypted, "password", &options).unwrap();
+
+        decrypt_file(&encrypted, &decrypted, "password").unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), b"memory-hard");
+        fs::remove_file(&decrypted).unwrap();
+        assert!(decrypt_file(&encrypted, &decrypted, "wrong").is_err());
+        assert!(!decrypted.exists());
+    }
+}
+
+#[test]
+fn cli_scrypt_selection() {
+    let args = KdfArgs { kdf: Kdf::Scrypt, scrypt_log_n: 12, billiard_prehash: true, ..Default::default() };
+    assert_eq!(args.params(), Ok(KdfParams::Scrypt { params: params(12, 8, 1), billiard_prehash: true }));
+    let bad = KdfArgs { kdf: Kdf::Scrypt, scrypt_r: 0, ..Default::default() };
+    assert!(bad.params().is_err());
+}
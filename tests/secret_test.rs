@@ -0,0 +1,62 @@
+use std::mem::ManuallyDrop;
+use crypto_app::core::crypto::secret::{Secret, Zeroize};
+use crypto_app::core::crypto::cipher::Cipher;
+
+#[test]
+fn zeroize_clears_values() {
+    let mut array = [0xaau8; 32];
+    array.zeroize();
+    assert_eq!(array, [0u8; 32]);
+
+    let mut words = [u32::MAX; 8];
+    words.zeroize();
+    assert_eq!(words, [0u32; 8]);
+
+    let mut keys = vec![[7u8; 16]; 3];
+    keys.zeroize();
+    assert!(keys.is_empty());
+
+    let mut password = String::from("hunter2");
+    password.zeroize();
+    assert!(password.is_empty());
+
+    let mut maybe = Some([1u8; 4]);
+    maybe.zeroize();
+    assert_eq!(maybe, Some([0u8; 4]));
+}
+
+#[test]
+fn vec_spare_capacity_is_wiped() {
+    let mut buf = vec![0x5au8; 64];
+    buf.truncate(10);
+    buf.zeroize();
+    assert!(buf.capacity() >= 64);
+    // Вся ёмкость, включая хвост после truncate, обнулена
+    let spare = buf.spare_capacity_mut();
+    assert!(spare.iter().all(|b| unsafe { b.assume_init() } == 0));
+}
+
+#[test]
+fn secret_wipes_on_drop() {
+    let mut slot = ManuallyDrop::new(Secret::new([0x42u8; 32]));
+    assert_eq!(**slot, [0x42u8; 32]);
+    unsafe { ManuallyDrop::drop(&mut slot) };
+    // Память самого значения остаётся в `slot`, деструктор уже отработал
+    let bytes = unsafe { std::slice::from_raw_parts(&*slot as *const Secret<[u8; 32]> as *const u8, 32) };
+    assert_eq!(bytes, &[0u8; 32]);
+}
+
+#[test]
+fn secret_behaves_like_its_contents() {
+    let mut secret = Secret::new(vec![1u8, 2, 3]);
+    secret.push(4);
+    assert_eq!(&secret[..], &[1, 2, 3, 4]);
+    assert_eq!(format!("{:?}", secret), "Secret(..)");
+
+    let key = Secret::new([9u8; 32]);
+    let mut a = [0u8; 16];
+    let mut b = a;
+    Cipher::new(*key).encrypt_block(&mut a);
+    Cipher::new([9u8; 32]).encrypt_block(&mut b);
+    assert_eq!(a, b);
+}
@@ -0,0 +1,110 @@
+// Векторы NIST: FIPS 180-2 (примеры из приложений) и CAVP SHA*ShortMsg.rsp
+use crypto_app::core::crypto::digest::Digest;
+use crypto_app::core::crypto::hmac::{HmacSha384, HmacSha512};
+use crypto_app::core::crypto::sha256::{Sha224, Sha256};
+use crypto_app::core::crypto::sha512::{Sha384, Sha512};
+use hex_literal::hex;
+use std::io::Write;
+
+const TWO_BLOCK_448: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+const TWO_BLOCK_896: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+#[test]
+fn sha224_short_messages() {
+    assert_eq!(Sha224::digest(b""), hex!("d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f"));
+    assert_eq!(Sha224::digest(&hex!("84")), hex!("3cd36921df5d6963e73739cf4d20211e2d8877c19cff087ade9d0e3a"));
+    assert_eq!(Sha224::digest(b"abc"), hex!("23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"));
+    assert_eq!(Sha224::digest(TWO_BLOCK_448), hex!("75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525"));
+}
+
+#[test]
+fn sha256_short_messages() {
+    assert_eq!(Sha256::digest(&hex!("d3")), hex!("28969cdfa74a12c82f3bad960b0b000aca2ac329deea5c2328ebc6f2ba9802c1"));
+    assert_eq!(Sha256::digest(&hex!("5fd4")), hex!("7c4fbf484498d21b487b9d61de8914b2eadaf2698712936d47c3ada2558f6788"));
+}
+
+#[test]
+fn sha384_short_messages() {
+    assert_eq!(Sha384::digest(b""), hex!(
+        "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b"));
+    assert_eq!(Sha384::digest(&hex!("c5")), hex!(
+        "b52b72da75d0666379e20f9b4a79c33a329a01f06a2fb7865c9062a28c1de860ba432edfd86b4cb1cb8a75b46076e3b1"));
+    assert_eq!(Sha384::digest(b"abc"), hex!(
+        "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"));
+    assert_eq!(Sha384::digest(TWO_BLOCK_896), hex!(
+        "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039"));
+}
+
+#[test]
+fn sha512_short_messages() {
+    assert_eq!(Sha512::digest(b""), hex!(
+        "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce"
+        "47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"));
+    assert_eq!(Sha512::digest(&hex!("21")), hex!(
+        "3831a6a6155e509dee59a7f451eb35324d8f8f2df6e3708894740f98fdee2388"
+        "9f4de5adb0c5010dfb555cda77c8ab5dc902094c52de3278f35a75ebc25f093a"));
+    assert_eq!(Sha512::digest(&hex!("9083")), hex!(
+        "55586ebba48768aeb323655ab6f4298fc9f670964fc2e5f2731e34dfa4b0c09e"
+        "6e1e12e3d7286b3145c61c2047fb1a2a1297f36da64160b31fa4c8c2cddd2fb4"));
+    assert_eq!(Sha512::digest(b"abc"), hex!(
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a"
+        "2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"));
+    assert_eq!(Sha512::digest(TWO_BLOCK_896), hex!(
+        "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018"
+        "501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"));
+}
+
+/// Миллион символов 'a' через `io::Write` порциями, не кратными блоку
+fn million_a<H: Digest + Write>() -> H::Output {
+    let mut hasher = H::new();
+    let chunk = [b'a'; 1000];
+    for _ in 0..1000 {
+        hasher.write_all(&chunk[..333]).unwrap();
+        hasher.write_all(&chunk[333..]).unwrap();
+    }
+    hasher.finalize()
+}
+
+#[test]
+fn long_messages() {
+    assert_eq!(million_a::<Sha224>(), hex!("20794655980c91d8bbb4c1ea97618a4bf03f42581948b2ee4ee7ad67"));
+    assert_eq!(million_a::<Sha256>(), hex!("cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"));
+    assert_eq!(million_a::<Sha384>(), hex!(
+        "9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b07b8b3dc38ecc4ebae97ddd87f3d8985"));
+    assert_eq!(million_a::<Sha512>(), hex!(
+        "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973eb"
+        "de0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b"));
+}
+
+/// Хеш от конкатенации хешей всех префиксов длиной 0..=300 байт: покрывает
+/// дополнение на границах блока. Ожидаемые значения посчитаны Python `hashlib`
+fn all_prefixes<H: Digest>() -> H::Output {
+    let message: Vec<u8> = (0..300u32).map(|i| (i * 7) as u8).collect();
+    let mut acc = H::new();
+    for len in 0..=message.len() {
+        acc.update(H::digest(&message[..len]).as_ref());
+    }
+    acc.finalize()
+}
+
+#[test]
+fn padding_boundaries() {
+    assert_eq!(all_prefixes::<Sha224>(), hex!("c83fc957349b3b16fccc1082fc7d1cbfab8d96a3155449103e9319dd"));
+    assert_eq!(all_prefixes::<Sha256>(), hex!("3b05b530351f50663ba5ac734c246d169eafec6b57c70dc6dc94cad39d04aad8"));
+    assert_eq!(all_prefixes::<Sha384>(), hex!(
+        "00ff192ce1d334e39d1ce94d135f2673fdd482a9f3b5bb8a86f1b38f7b7d1ccc0c1df396672e98a3d26b1bf65bfa0acf"));
+    assert_eq!(all_prefixes::<Sha512>(), hex!(
+        "8a732a2ef96b72c61264041d982c51f52f584ba520746f8dfcbe356c60311e9d"
+        "68a05c40ac64998fb6bfca078a50d008373e7d30159f0e4626b1102c1cf370b3"));
+}
+
+// RFC 4231, тест 2
+#[test]
+fn hmac_sha384_and_sha512() {
+    let message = b"what do ya want for nothing?";
+    assert_eq!(HmacSha384::mac(b"Jefe", message), hex!(
+        "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649"));
+    assert_eq!(HmacSha512::mac(b"Jefe", message), hex!(
+        "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554"
+        "9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"));
+}
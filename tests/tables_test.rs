@@ -0,0 +1,144 @@
+use crypto_app::core::crypto::cipher::{Cipher, SpnParams};
+use crypto_app::core::crypto::p_box::TRANSPOSE_P_BOX;
+use crypto_app::core::crypto::s_box::{S_BOX, generate_s_box};
+use crypto_app::core::io::file::{encrypt_file, encrypt_file_with, decrypt_file, decrypt_file_with, EncryptOptions, DecryptOptions};
+use crypto_app::core::io::meta::{Metadata, PBoxId, SBoxId, FLAG_TABLE_FINGERPRINT};
+use crypto_app::core::io::tables::{CustomTables, parse_s_box, parse_p_box, parse_table, load_s_box};
+use tempfile::TempDir;
+use std::fs;
+
+fn s_box_json(s_box: &[u8; 256]) -> String {
+    let values: Vec<String> = s_box.iter().map(|v| v.to_string()).collect();
+    format!("[{}]", values.join(", "))
+}
+
+fn custom_tables() -> CustomTables {
+    CustomTables { s_box: Some(generate_s_box(&[7; 32])), p_box: Some(TRANSPOSE_P_BOX) }
+}
+
+#[test]
+fn both_formats_parse_to_the_same_table() {
+    let json = s_box_json(&S_BOX);
+    let spaced: String = S_BOX.chunks(16)
+        .map(|row| row.iter().map(|v| format!("0x{:02x},", v)).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let compact = format!("# AES S-box\n{}\n", hex::encode(S_BOX));
+
+    assert_eq!(parse_s_box(&json).unwrap(), S_BOX);
+    assert_eq!(parse_s_box(&spaced).unwrap(), S_BOX);
+    assert_eq!(parse_s_box(&compact).unwrap(), S_BOX);
+
+    let p_box_hex: String = TRANSPOSE_P_BOX.iter().map(|v| format!("{:02x} ", v)).collect();
+    assert_eq!(parse_p_box(&p_box_hex).unwrap(), TRANSPOSE_P_BOX);
+    assert_eq!(parse_table("[]").unwrap(), Vec::<usize>::new());
+}
+
+#[test]
+fn malformed_tables_are_rejected() {
+    let mut duplicate = S_BOX;
+    duplicate[10] = duplicate[11];
+    let err = parse_s_box(&s_box_json(&duplicate)).unwrap_err();
+    assert!(err.contains("not a permutation"), "{}", err);
+
+    let err = parse_s_box(&hex::encode(&S_BOX[..255])).unwrap_err();
+    assert!(err.contains("256 entries, got 255"), "{}", err);
+
+    let mut p_box: Vec<String> = TRANSPOSE_P_BOX.iter().map(|v| v.to_string()).collect();
+    p_box[3] = "128".into();
+    let err = parse_p_box(&format!("[{}]", p_box.join(","))).unwrap_err();
+    assert!(err.contains("out of range"), "{}", err);
+
+    assert!(parse_table("[1, 2, x]").is_err());
+    assert!(parse_table("[1, 2").is_err());
+    assert!(parse_table("abc").is_err());
+    assert!(parse_table("zz").is_err());
+    assert!(parse_table("a€").is_err());
+    assert!(parse_table("0x").is_err());
+}
+
+#[test]
+fn tables_load_from_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("s_box.json");
+    fs::write(&path, s_box_json(&S_BOX)).unwrap();
+    assert_eq!(load_s_box(&path).unwrap(), S_BOX);
+    assert!(load_s_box(&dir.path().join("missing.json")).is_err());
+}
+
+#[test]
+fn loaded_tables_drive_the_cipher() {
+    let tables = custom_tables();
+    let params = SpnParams::hardened(10)
+        .with_s_box(tables.s_box.unwrap())
+        .with_p_box(tables.p_box.unwrap());
+    let cipher = Cipher::with_params([1; 32], &params).unwrap();
+
+    let mut block = *b"custom SPN table";
+    cipher.encrypt_block(&mut block);
+    cipher.decrypt_block(&mut block);
+    assert_eq!(&block, b"custom SPN table");
+}
+
+#[test]
+fn fingerprint_depends_on_every_table() {
+    let tables = custom_tables();
+    let s_only = CustomTables { p_box: None, ..tables.clone() };
+    let p_only = CustomTables { s_box: None, ..tables.clone() };
+    let mut other_s_box = tables.clone();
+    other_s_box.s_box.as_mut().unwrap().swap(0, 1);
+
+    let fingerprints = [tables.fingerprint(), s_only.fingerprint(), p_only.fingerprint(), other_s_box.fingerprint()];
+    for i in 0..fingerprints.len() {
+        assert!(fingerprints[i + 1..].iter().all(|f| *f != fingerprints[i]));
+    }
+    assert_eq!(tables.fingerprint(), custom_tables().fingerprint());
+}
+
+#[test]
+fn file_records_and_checks_the_table_fingerprint() {
+    let dir = TempDir::new().unwrap();
+    let plain = dir.path().join("plain.txt");
+    let encrypted = dir.path().join("plain.enc");
+    let decrypted = dir.path().join("plain.dec");
+    fs::write(&plain, b"tables from a student lab").unwrap();
+
+    let tables = custom_tables();
+    let options = EncryptOptions { tables: tables.clone(), ..Default::default() };
+    encrypt_file_with(&plain, &encrypted, "password", &options).unwrap();
+
+    let header = Metadata::from_bytes(&fs::read(&encrypted).unwrap()).unwrap();
+    assert_eq!(header.cipher_params.s_box, SBoxId::Custom);
+    assert_eq!(header.cipher_params.p_box, PBoxId::Custom);
+    assert_eq!(header.flags & FLAG_TABLE_FINGERPRINT, FLAG_TABLE_FINGERPRINT);
+    assert_eq!(header.table_fingerprint, Some(tables.fingerprint()));
+
+    let err = decrypt_file(&encrypted, &decrypted, "password").unwrap_err();
+    assert!(err.contains("supply them"), "{}", err);
+
+    let mut wrong = tables.clone();
+    wrong.p_box.as_mut().unwrap().swap(0, 1);
+    let err = decrypt_file_with(&encrypted, &decrypted, "password", &DecryptOptions { tables: wrong, ..Default::default() }).unwrap_err();
+    assert!(err.contains("do not match"), "{}", err);
+    assert!(!decrypted.exists());
+
+    decrypt_file_with(&encrypted, &decrypted, "password", &DecryptOptions { tables: tables.clone(), ..Default::default() }).unwrap();
+    assert_eq!(fs::read(&decrypted).unwrap(), b"tables from a student lab");
+
+    // Таблицы для файла со встроенными таблицами — тоже ошибка
+    encrypt_file(&plain, &encrypted, "password").unwrap();
+    let err = decrypt_file_with(&encrypted, &decrypted, "password", &DecryptOptions { tables, ..Default::default() }).unwrap_err();
+    assert!(err.contains("not encrypted with custom"), "{}", err);
+}
+
+#[test]
+fn fingerprint_section_roundtrip() {
+    let mut metadata = Metadata::new();
+    metadata.set_table_fingerprint(Some([0x42; 32]));
+    let bytes = metadata.to_bytes();
+    assert_eq!(bytes.len(), Metadata::new().header_len() + 33);
+    assert_eq!(Metadata::from_bytes(&bytes).unwrap(), metadata);
+
+    metadata.set_table_fingerprint(None);
+    assert_eq!(metadata.flags & FLAG_TABLE_FINGERPRINT, 0);
+}